@@ -1,16 +1,19 @@
 // vim:fileencoding=utf-8:foldmethod=marker
 // imports {{{
+use crate::tui_app::keymap::Keymap;
 use crate::Args;
-use c3::todo_app::{App, Restriction, Todo, TodoList};
-use c3::{DisplayArgs, DoOnSelected, TodoDisplay};
-use clap::Parser;
-use clap::{Command, CommandFactory};
+use c3::todo_app::{links, template, App, Restriction, Schedule, Todo, TodoList};
+use c3::{date, DisplayArgs, DoOnSelected, TodoDisplay};
+use clap::{ArgGroup, ArgMatches, FromArgMatches, Parser, Subcommand};
+use clap::{error::ErrorKind, Command, CommandFactory};
 use clap_complete::Shell;
 use clap_complete::{generate, Generator};
 use std::io;
 use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
+mod notify;
+use notify::NotifyArgs;
 // }}}
 
 #[derive(Parser, Debug)]
@@ -23,6 +26,10 @@ pub struct CliArgs {
     #[arg(long)]
     do_on_selected: Option<DoOnSelected>,
 
+    /// Set a reminder on selected todos (like today, +3d, fri 14:30, dec 24 or 2026-12-24)
+    #[arg(long, requires = "search_and_select")]
+    reminder: Option<String>,
+
     #[arg(short = 'b', long, default_value_t = false)]
     batch_edit: bool,
 
@@ -60,11 +67,122 @@ pub struct CliArgs {
     /// Generate completion for a certain shell
     #[arg(short = 'c', long)]
     completion: Option<Shell>,
+
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+impl CliArgs {
+    /// Updates the options that config_matches has, see Config::matches. Without a subcommand,
+    /// the derived update fails to make one after updating the other options.
+    pub fn update_from_config(&mut self, config_matches: &ArgMatches) -> Result<(), clap::Error> {
+        match self.update_from_arg_matches(config_matches) {
+            Err(error) if error.kind() == ErrorKind::MissingSubcommand && self.command.is_none() => Ok(()),
+            result => result,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    /// Add a todo to the todo list
+    Add {
+        /// Message of the todo. With --template, replaces the message of the template's todo
+        message: Option<String>,
+
+        /// Add a copy of this template with its whole dependency tree
+        #[arg(short, long)]
+        template: Option<String>,
+
+        /// Fill {{key}} placeholders of the template (like version=1.8)
+        #[arg(long)]
+        var: Vec<String>,
+    },
+    /// Save the first todo matching the query with its dependency tree as a template
+    SaveTemplate {
+        /// Name of the template
+        name: String,
+
+        /// Search query of the todo, matched against the whole tree
+        query: String,
+    },
+    /// Move the first todo of the whole tree that matches the query (with its dependencies)
+    #[command(group(ArgGroup::new("destination").required(true).args(["parent", "into", "file"])))]
+    Move {
+        /// Search query of the todo, matched against the whole tree
+        query: String,
+
+        /// Move it to the list of its parent
+        #[arg(long)]
+        parent: bool,
+
+        /// Move it into the todo list of the first todo of the whole tree that matches this
+        #[arg(long)]
+        into: Option<String>,
+
+        /// Move it to another todo file, its notes go to the notes dir of that file
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// List todos of the whole tree
+    Ls {
+        /// Only list todos with this tag or context (like work, #work or @home)
+        #[arg(short, long)]
+        tag: Vec<String>,
+
+        /// Only list todos with this attribute (like owner:bob, or estimate: for any estimate)
+        #[arg(short, long)]
+        attribute: Vec<String>,
+    },
+    /// List todos of the whole tree that were completed since a date
+    DoneLog {
+        /// Like 7d, 2w, monday or 2026-10-01
+        #[arg(long, default_value = "7d")]
+        since: String,
+    },
+    /// Move done todos that aren't scheduled (with their dependencies) of the whole tree to the
+    /// archive, open it with --archive
+    Archive {
+        /// Only archive todos completed before this, like 30d, 2w or 2026-10-01
+        #[arg(long)]
+        older_than: Option<String>,
+    },
+    /// Print the keys of the TUI as markdown tables, like in the README
+    Keys {
+        /// Ignore the keymap file
+        #[arg(long)]
+        default: bool,
+    },
+    /// Check blocked-by links of the whole tree for unknown ids and cycles
+    Fsck,
+    /// Show completion history and streaks of scheduled todos
+    Stats {
+        /// Search query of the todo, matched against the whole tree
+        id: String,
+    },
+    /// Notify reminders of the whole tree that are due and aren't notified yet
+    Notify {
+        #[command(flatten)]
+        notify_args: NotifyArgs,
+    },
+    /// Keep running and notify reminders of the whole tree when they are due
+    Daemon {
+        #[command(flatten)]
+        notify_args: NotifyArgs,
+
+        /// Seconds between each check
+        #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
 }
 
 pub struct NotCli;
 #[inline]
 pub fn run(app: &mut App, args: CliArgs) -> Result<(), NotCli> {
+    if let Some(command) = args.command {
+        run_command(app, command);
+        return Ok(());
+    }
     if !args.search_and_select.is_empty() {
         for query in args.search_and_select {
             app.set_restriction(Rc::new(move |todo| todo.matches(query.as_str())))
@@ -73,6 +191,17 @@ pub fn run(app: &mut App, args: CliArgs) -> Result<(), NotCli> {
             process::exit(1);
         }
         let restriction = app.get_restriction().clone();
+        if let Some(date) = args.reminder.as_deref() {
+            let (date, time) = parse_date_arg(date, app.args.display_args.calendar(), date::parse_user_input_with_time);
+            let mut list = app.current_list_mut();
+            list.changed = true;
+            for todo in list.todos_mut(&restriction) {
+                let mut schedule = Schedule::new_reminder(date);
+                schedule.set_time(time);
+                todo.schedule = Some(schedule);
+            }
+            list.sort();
+        }
         if let Some(do_on_selected) = args.do_on_selected {
             match do_on_selected {
                 DoOnSelected::Delete => app
@@ -80,12 +209,22 @@ pub fn run(app: &mut App, args: CliArgs) -> Result<(), NotCli> {
                     .todos
                     .retain(|todo| !restriction(todo)),
                 DoOnSelected::Done => {
-                    for todo in app.current_list_mut().todos_mut(&restriction) {
+                    let to_children = app.args.done_propagation.to_children();
+                    let record_completed = app.args.record_created;
+                    let mut list = app.current_list_mut();
+                    list.changed = true;
+                    for todo in list.todos_mut(&restriction) {
                         todo.set_done(true);
+                        if record_completed {
+                            todo.ensure_completed();
+                        }
+                        if to_children {
+                            todo.set_dependencies_done(true, record_completed);
+                        }
                     }
                 }
             }
-        } else {
+        } else if args.reminder.is_none() {
             print_todos(app);
             return Ok(());
         }
@@ -129,6 +268,183 @@ pub fn run(app: &mut App, args: CliArgs) -> Result<(), NotCli> {
     Err(NotCli)
 }
 
+// Dates are read in the calendar set by the args
+fn parse_date_arg<T>(input: &str, calendar: date::Calendar, parser: fn(&str, date::Calendar) -> Result<T, date::Error>) -> T {
+    parser(input, calendar).unwrap_or_else(|err| {
+        Args::command()
+            .error(ErrorKind::InvalidValue, format!("invalid date '{input}': {err}"))
+            .exit()
+    })
+}
+
+fn run_command(app: &mut App, command: CliCommand) {
+    match command {
+        CliCommand::Add { message, template, var } => add_todo(app, message, template, var),
+        CliCommand::SaveTemplate { name, query } => save_template(app, &name, &query),
+        CliCommand::Move { query, parent, into, file } => move_todo(app, &query, parent, into, file),
+        CliCommand::Ls { tag, attribute } => print_tree_todos(app, tag, attribute),
+        CliCommand::DoneLog { since } => print_done_log(app, &since),
+        CliCommand::Archive { older_than } => archive(app, older_than.as_deref()),
+        CliCommand::Keys { default } => print_keys(default),
+        CliCommand::Fsck => fsck(app),
+        CliCommand::Stats { id } => print_stats(app, &id),
+        CliCommand::Notify { notify_args } => {
+            notify::notify(app, &notify_args).expect("Failed to write notified reminders")
+        }
+        CliCommand::Daemon {
+            notify_args,
+            interval,
+        } => notify::daemon(app, &notify_args, interval).expect("Failed to write notified reminders"),
+    }
+}
+
+fn add_todo(app: &mut App, message: Option<String>, template: Option<String>, vars: Vec<String>) {
+    match (template, message) {
+        (Some(name), message) => {
+            let vars = template::parse_vars(vars.iter().map(String::as_str));
+            if !app.append_template(&name, message.as_deref(), &vars) {
+                eprintln!("Template \"{name}\" not found");
+                process::exit(1);
+            }
+        }
+        (None, Some(message)) => {
+            let todo = app.new_todo(message, 0);
+            let mut list = app.current_list_mut();
+            list.push(todo);
+            list.sort();
+        }
+        (None, None) => {
+            Args::command()
+                .error(ErrorKind::MissingRequiredArgument, "add needs a message or --template")
+                .exit()
+        }
+    }
+    app.write().expect("Failed to write file.");
+}
+
+fn save_template(app: &mut App, name: &str, query: &str) {
+    if !template::is_valid_name(name) {
+        eprintln!("Invalid template name \"{name}\"");
+        process::exit(1);
+    }
+    let todo = app.todo_list.tree_find_map(&mut |todo| todo.matches(query).then(|| todo.clone()));
+    let Some(todo) = todo else {
+        eprintln!("No todo matches \"{query}\"");
+        process::exit(1);
+    };
+    let mut list = TodoList::new();
+    list.push(todo);
+    app.write_template(name, &list).expect("Failed to write the template");
+}
+
+fn move_todo(app: &mut App, query: &str, parent: bool, into: Option<String>, file: Option<PathBuf>) {
+    let position = app.todo_list.tree_position(&|_, todo| todo.matches(query));
+    if !position.is_some_and(|position| app.go_to(&position)) {
+        eprintln!("No todo matches \"{query}\"");
+        process::exit(1);
+    }
+    let moved = match (into, file) {
+        (Some(into), _) => app.move_to_match(&into),
+        (_, Some(file)) => app.move_to_file(&file).expect("Failed to write the todo file") > 0,
+        _ => parent && app.move_to_parent(),
+    };
+    if !moved {
+        eprintln!("Can't move the todo there");
+        process::exit(1);
+    }
+    app.write().expect("Failed to write file.");
+}
+
+fn print_tree_todos(app: &mut App, tags: Vec<String>, attributes: Vec<String>) {
+    for tag in tags {
+        app.set_restriction_with_last(Rc::new(move |todo| todo.has_tag(&tag)), None)
+    }
+    for attribute in attributes {
+        app.set_restriction_with_last(Rc::new(move |todo| todo.attributes().matches(&attribute)), None)
+    }
+    let restriction = app.get_restriction().clone();
+    app.todo_list.tree_for_each(|todo| {
+        if restriction(todo) {
+            println!("{}", todo.display_with_args(&app.args.display_args));
+        }
+    });
+}
+
+// "7d" means 7 days ago and "fri" the last friday, anything else is read as a date
+#[inline]
+fn parse_past_date_arg(input: &str, calendar: date::Calendar) -> date::Type {
+    parse_date_arg(input, calendar, date::parse_past_user_input)
+}
+
+fn print_done_log(app: &App, since: &str) {
+    let since = parse_past_date_arg(since, app.args.display_args.calendar());
+    let mut done: Vec<(date::Type, String)> = vec![];
+    app.todo_list.tree_for_each(|todo| {
+        match todo.schedule.as_ref().and_then(|schedule| schedule.history()) {
+            Some(history) => done.extend(history.dates().iter().map(|&date| (date, todo.message.clone()))),
+            None => done.extend(todo.completed().map(|date| (date, todo.message.clone()))),
+        }
+    });
+    done.retain(|(date, _)| *date >= since);
+    done.sort();
+    for (date, message) in done {
+        println!("{} {message}", date::display(Some(date), app.args.display_args.date_format(), app.args.display_args.calendar()));
+    }
+}
+
+fn archive(app: &mut App, older_than: Option<&str>) {
+    let before = older_than.map(|older_than| parse_past_date_arg(older_than, app.args.display_args.calendar()));
+    let count = app.archive_done(before).expect("Failed to write the archive");
+    println!("Archived {count} todos");
+}
+
+fn print_keys(default: bool) {
+    let keymap = match default {
+        true => Keymap::default(),
+        false => Keymap::read(&Keymap::default_path()).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            process::exit(1)
+        }),
+    };
+    println!("{}", keymap.markdown(false));
+    println!("{}", keymap.markdown(true));
+}
+
+fn fsck(app: &App) {
+    let problems = links::check(&app.todo_list);
+    for problem in problems.iter() {
+        println!("{problem}");
+    }
+    if !problems.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn print_stats(app: &App, query: &str) {
+    let mut found = false;
+    app.todo_list.tree_for_each(|todo| {
+        if !todo.matches(query) {
+            return;
+        }
+        if let Some((schedule, stats)) = todo
+            .schedule
+            .as_ref()
+            .and_then(|schedule| Some((schedule, schedule.stats()?)))
+        {
+            found = true;
+            println!("{}", todo.message);
+            println!("  Schedule:{}", schedule.display(app.args.display_args.date_format(), app.args.display_args.calendar()));
+            println!("  Completions: {}", stats.completions);
+            println!("  Current streak: {}", stats.current_streak);
+            println!("  Longest streak: {}", stats.longest_streak);
+            println!("  Completion rate: {:.0}%", stats.rate * 100.);
+        }
+    });
+    if !found {
+        process::exit(1);
+    }
+}
+
 fn print_completions<G: Generator>(gen: G, cmd: &mut Command) {
     generate(gen, cmd, cmd.get_name().to_string(), &mut io::stdout());
 }
@@ -191,7 +507,7 @@ impl PrintTodoTree {
 
             if let Some(todo_list) = todo.dependency.as_ref().and_then(|dep| dep.todo_list()) {
                 let mut tree_child = self.tree_child();
-                tree_child.print_list(todo_list, display_args, restriction);
+                tree_child.print_list(&todo_list, display_args, restriction);
             } else if let Some(note) = todo.dependency.as_ref().and_then(|dep| dep.note()) {
                 self.print_note(note)
             }
@@ -250,3 +566,16 @@ impl PrintTodoTree {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daemon_interval() {
+        let args = Args::try_parse_from(["c3", "daemon", "--interval", "5"]).unwrap();
+        assert!(matches!(args.cli_args.command, Some(CliCommand::Daemon { interval: 5, .. })));
+        let error = Args::try_parse_from(["c3", "daemon", "--interval", "0"]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+    }
+}
@@ -0,0 +1,94 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use c3::{date, fileio, todo_app::App};
+use clap::Args;
+use std::collections::BTreeSet;
+use std::fs::{self, create_dir_all};
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+// }}}
+
+#[derive(Args, Debug)]
+pub struct NotifyArgs {
+    /// Command to notify with, run by sh with the todo message as its last argument
+    #[arg(long, default_value_t = String::from("notify-send c3"))]
+    notify_command: String,
+}
+
+/// Reminders that are already notified, so they don't fire twice. Each line is
+/// `<reminder date> <time or -> <todo hash>`, only the reminders that are still due are kept.
+struct Notified {
+    path: PathBuf,
+    keys: BTreeSet<String>,
+}
+
+impl Notified {
+    #[inline]
+    fn read(app: &App) -> Self {
+        let path = fileio::append_notes_to_path_parent(&app.args.todo_path).join("notified");
+        let keys = fileio::file_content(&path)
+            .map(|content| content.lines().map(String::from).collect())
+            .unwrap_or_default();
+        Self { path, keys }
+    }
+
+    #[inline]
+    fn write(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            create_dir_all(parent)?;
+        }
+        let content: String = self.keys.iter().map(|key| format!("{key}\n")).collect();
+        fs::write(&self.path, content)
+    }
+}
+
+/// Runs the notify command for every due reminder in the whole tree that isn't done or notified
+/// yet. Reminders of the days before are due too, in case nothing was running on their day.
+pub fn notify(app: &App, args: &NotifyArgs) -> io::Result<()> {
+    let mut notified = Notified::read(app);
+    let now = date::now();
+    let mut due = BTreeSet::new();
+    app.todo_list.tree_for_each(|todo| {
+        let Some(schedule) = todo.schedule.as_ref().filter(|_| todo.is_due_reminder(now)) else {
+            return;
+        };
+        let time = schedule.time().map_or(String::from("-"), date::format_time);
+        let key = format!("{} {time} {}", date::format(schedule.date()), todo.hash());
+        if !notified.keys.contains(&key) {
+            run_notify_command(&args.notify_command, &todo.message);
+        }
+        due.insert(key);
+    });
+    if due != notified.keys {
+        notified.keys = due;
+        notified.write()?;
+    }
+    Ok(())
+}
+
+pub fn daemon(app: &mut App, args: &NotifyArgs, interval: u64) -> io::Result<()> {
+    loop {
+        app.read();
+        notify(app, args)?;
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+#[inline]
+fn run_notify_command(command: &str, message: &str) {
+    // The message is $1 of the script, so it's never read as shell code
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{command} \"$1\""))
+        .arg("sh")
+        .arg(message)
+        .status();
+    match status {
+        Ok(status) if !status.success() => eprintln!("\"{command}\" failed with {status}"),
+        Err(err) => eprintln!("Couldn't run \"{command}\": {err}"),
+        _ => {}
+    }
+}
@@ -0,0 +1,239 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use crate::fileio;
+use clap::builder::Resettable;
+use clap::{error::ErrorKind, parser::ValueSource, value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+// }}}
+
+/// Options of the config file, named like their long command line options
+/// (`sort-method = "attribute"`, `show-done = true`). Options of a `[profile."<todo path>"]`
+/// table override them for that todo file, and command line options override both.
+#[derive(Debug, Default)]
+pub struct Config {
+    table: toml::Table,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/c3/config.toml`
+    #[inline]
+    pub fn default_path() -> PathBuf {
+        fileio::config_dir().join("config.toml")
+    }
+
+    /// Reads the config file, an empty config if it doesn't exist
+    pub fn read(path: &Path) -> io::Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {error}", path.display())))
+    }
+
+    /// Tables of the config that aren't options, like `[themes]`
+    #[inline]
+    pub fn table(&self, name: &str) -> Option<&toml::Table> {
+        self.table.get(name)?.as_table()
+    }
+
+    /// The todo path that is used when none is given
+    #[inline]
+    pub fn todo_path(&self) -> Option<PathBuf> {
+        self.table.get("todo-path")?.as_str().map(fileio::expand_home)
+    }
+
+    /// The profile table of the todo path
+    fn profile(&self, todo_path: &Path) -> Option<&toml::Table> {
+        let todo_path = todo_path.canonicalize().unwrap_or(todo_path.to_path_buf());
+        self.table
+            .get("profile")?
+            .as_table()?
+            .iter()
+            .find(|(path, _)| {
+                let path = fileio::expand_home(path);
+                path.canonicalize().unwrap_or(path) == todo_path
+            })?
+            .1
+            .as_table()
+    }
+
+    /// Parses the options of the config (and of the profile of todo path) that matches,
+    /// parsed by command, doesn't have from the command line. Updating the parsed arguments from
+    /// the returned matches fills them in. Options of a subcommand are only used with it.
+    pub fn matches(&self, command: Command, matches: &ArgMatches, todo_path: &Path) -> Result<ArgMatches, clap::Error> {
+        let mut options = self.table.clone();
+        if let Some(profile) = self.profile(todo_path) {
+            options.extend(profile.clone());
+        }
+        let subcommand = matches
+            .subcommand()
+            .and_then(|(name, sub_matches)| Some((command.find_subcommand(name)?, sub_matches)));
+        let mut args: Vec<OsString> = vec![command.get_name().into()];
+        let mut subcommand_args = vec![];
+        for (key, value) in &options {
+            if key == "todo-path" || value.is_table() {
+                continue;
+            }
+            if let Some(arg) = option(&command, key) {
+                if !is_set(matches, arg) {
+                    push_option(&mut args, key, value);
+                }
+            } else if let Some((arg, sub_matches)) =
+                subcommand.and_then(|(sub, sub_matches)| Some((option(sub, key)?, sub_matches)))
+            {
+                if !is_set(sub_matches, arg) {
+                    push_option(&mut subcommand_args, key, value);
+                }
+            } else if !command.get_subcommands().any(|sub| option(sub, key).is_some()) {
+                return Err(command.clone().error(ErrorKind::UnknownArgument, format!("unknown option '{key}' in the config")));
+            }
+        }
+        if let Some((sub, _)) = subcommand {
+            args.push(sub.get_name().into());
+            args.append(&mut subcommand_args);
+        }
+        config_command(command).try_get_matches_from(args)
+    }
+}
+
+impl std::str::FromStr for Config {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self { table: s.parse()? })
+    }
+}
+
+/// The long option key of command, not one of its subcommands
+#[inline]
+fn option<'a>(command: &'a Command, key: &str) -> Option<&'a Arg> {
+    command.get_arguments().find(|arg| arg.get_long() == Some(key))
+}
+
+#[inline]
+fn is_set(matches: &ArgMatches, arg: &Arg) -> bool {
+    matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+}
+
+/// Arrays are given as the option repeated, they replace the defaults like on the command line
+fn push_option(args: &mut Vec<OsString>, key: &str, value: &toml::Value) {
+    match value {
+        toml::Value::Array(values) => {
+            for value in values {
+                args.push(format!("--{key}={}", value_string(value)).into());
+            }
+        }
+        value => args.push(format!("--{key}={}", value_string(value)).into()),
+    }
+}
+
+/// command without defaults or required arguments, so its matches only have the options of the
+/// config. Flags take true or false, so the config can turn them off.
+fn config_command(command: Command) -> Command {
+    let groups: Vec<_> = command.get_groups().map(|group| group.get_id().clone()).collect();
+    let subcommands: Vec<_> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    let mut command = command.mut_args(|arg| {
+        let arg = arg.required(false).default_value(None::<&str>).requires(Resettable::Reset);
+        match arg.get_action() {
+            ArgAction::SetTrue => arg.action(ArgAction::Set).value_parser(value_parser!(bool)),
+            _ => arg,
+        }
+    });
+    for group in groups {
+        command = command.mut_group(group, |group| group.required(false));
+    }
+    for sub in subcommands {
+        command = command.mut_subcommand(sub, config_command);
+    }
+    command
+}
+
+#[inline]
+fn value_string(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+
+    #[derive(Parser, Debug)]
+    struct TestArgs {
+        #[arg(short = 'd', long)]
+        show_done: bool,
+        #[arg(long, default_value = "normal")]
+        sort_method: String,
+        #[arg(long)]
+        done_string: Option<String>,
+        #[command(subcommand)]
+        command: Option<TestCommand>,
+    }
+
+    #[derive(Subcommand, Debug, PartialEq)]
+    enum TestCommand {
+        Ls {
+            #[arg(long)]
+            tag: Vec<String>,
+        },
+    }
+
+    fn parse(config: &Config, args: &[&str], todo_path: &str) -> Result<TestArgs, clap::Error> {
+        let matches = TestArgs::command().try_get_matches_from(args)?;
+        let mut parsed = TestArgs::from_arg_matches(&matches)?;
+        let config_matches = config.matches(TestArgs::command(), &matches, Path::new(todo_path))?;
+        // Like CliArgs::update_from_config
+        match parsed.update_from_arg_matches(&config_matches) {
+            Err(error) if error.kind() == ErrorKind::MissingSubcommand && parsed.command.is_none() => Ok(parsed),
+            result => result.map(|_| parsed),
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let config: Config = r#"
+            todo-path = "~/todo"
+            sort-method = "attribute"
+            show-done = true
+            tag = ["work", "home"]
+
+            [themes.mine]
+            done = "dim"
+
+            [profile."/tmp/c3-test-profile/todo"]
+            sort-method = "reverse"
+            show-done = false
+            done-string = "[v] "
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(config.todo_path(), Some(fileio::expand_home("~/todo")));
+
+        let args = parse(&config, &["c3"], "/tmp/other").unwrap();
+        assert!(args.show_done);
+        assert_eq!(args.sort_method, "attribute");
+        assert_eq!(args.done_string, None);
+        assert_eq!(args.command, None);
+
+        let args = parse(&config, &["c3", "--sort-method", "alphabetical"], "/tmp/c3-test-profile/todo").unwrap();
+        assert!(!args.show_done);
+        assert_eq!(args.sort_method, "alphabetical");
+        assert_eq!(args.done_string.as_deref(), Some("[v] "));
+
+        let args = parse(&config, &["c3", "ls"], "/tmp/other").unwrap();
+        assert_eq!(args.command, Some(TestCommand::Ls { tag: vec!["work".into(), "home".into()] }));
+        let args = parse(&config, &["c3", "-d", "ls", "--tag", "fun"], "/tmp/other").unwrap();
+        assert!(args.show_done);
+        assert_eq!(args.command, Some(TestCommand::Ls { tag: vec!["fun".into()] }));
+
+        let config: Config = "colour = true".parse().unwrap();
+        assert!(parse(&config, &["c3"], "/tmp/other").is_err());
+    }
+}
@@ -1,17 +1,231 @@
-use chrono::format::ParseError;
-use chrono::{Duration, Local, NaiveDate};
+use chrono::format::{Item, ParseError, StrftimeItems};
+use chrono::{Datelike, Days, Duration, Local, Month, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use clap::ValueEnum;
+use std::fmt;
+use std::str::FromStr;
+mod jalali;
 const FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
 
 pub type Type = NaiveDate;
+pub type Time = NaiveTime;
+pub type DateTime = NaiveDateTime;
+
+/// Calendar dates are shown and typed in. Dates are always written to files in Gregorian.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Default)]
+pub enum Calendar {
+    #[default]
+    #[value(alias = "g")]
+    Gregorian,
+    #[value(alias = "j", alias = "persian")]
+    Jalali,
+}
+
+/// How dates are shown. This never changes the format dates are written to files in.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub enum DisplayFormat {
+    #[default]
+    Default,
+    /// Like "today", "in 3d" or "2w ago"
+    Relative,
+    /// A strftime format string
+    Custom(String),
+}
+
+impl FromStr for DisplayFormat {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "" | "default" => Ok(Self::Default),
+            "relative" => Ok(Self::Relative),
+            format if StrftimeItems::new(format).any(|item| item == Item::Error) => {
+                Err(format!("'{format}' is not a valid strftime format"))
+            }
+            format => Ok(Self::Custom(format.to_string())),
+        }
+    }
+}
+
+impl DisplayFormat {
+    /// Checks that the format can be shown in calendar, Jalali dates can't show gregorian week
+    /// numbers for example
+    pub fn check_calendar(&self, calendar: Calendar) -> Result<(), String> {
+        match (self, calendar) {
+            (Self::Custom(format), Calendar::Jalali) => jalali::format(current(), format).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    ParseFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "couldn't understand the date")
+    }
+}
+
+impl std::error::Error for Error {}
 
 #[inline]
 pub fn parse(date_string: &str) -> Result<Type, ParseError> {
     NaiveDate::parse_from_str(date_string, FORMAT)
 }
 
+/// Parses dates the way a user would type them. Accepts `2026-12-24`, `2026-12`, `12-24`,
+/// `dec 24`, `today`, `tomorrow`, `yesterday`, `+3d`, `-1w`, `+2m`, `in 5 days`, `fri` and
+/// `next fri` (the friday of next week). Numeric dates are read in calendar.
+#[inline(always)]
+pub fn parse_user_input(date_string: &str, calendar: Calendar) -> Result<Type, Error> {
+    parse_user_input_from(date_string, current(), calendar)
+}
+
+/// Like [`parse_user_input`], with an optional time at the end (like `fri 14:30`). A time on
+/// its own is for today.
+pub fn parse_user_input_with_time(input: &str, calendar: Calendar) -> Result<(Type, Option<Time>), Error> {
+    let input = input.trim();
+    let (date_string, time_string) = input.rsplit_once(' ').unwrap_or(("", input));
+    match parse_time(time_string) {
+        Ok(time) if date_string.trim().is_empty() => Ok((current(), Some(time))),
+        Ok(time) => Ok((parse_user_input(date_string, calendar)?, Some(time))),
+        Err(_) => Ok((parse_user_input(input, calendar)?, None)),
+    }
+}
+
+#[inline]
+pub fn parse_time(time_string: &str) -> Result<Time, ParseError> {
+    NaiveTime::parse_from_str(time_string.trim(), TIME_FORMAT)
+}
+
+#[inline]
+pub fn format_time(time: Time) -> String {
+    time.format(TIME_FORMAT).to_string()
+}
+
+/// Like [`parse_user_input`] for dates in the past, `7d` is 7 days ago and weekdays like `fri`
+/// are their last occurrence (today for today's weekday).
 #[inline(always)]
-pub fn parse_user_input(date_string: &str) -> Result<Type, ParseError> {
-    parse(date_string)
+pub fn parse_past_user_input(date_string: &str, calendar: Calendar) -> Result<Type, Error> {
+    parse_past_user_input_from(date_string, current(), calendar)
+}
+
+pub fn parse_past_user_input_from(date_string: &str, today: Type, calendar: Calendar) -> Result<Type, Error> {
+    let input = date_string.trim().to_lowercase();
+    if let Ok(weekday) = input.parse::<Weekday>() {
+        return Ok(previous_weekday(today, weekday));
+    }
+    parse_user_input_from(&format!("-{input}"), today, calendar)
+        .or_else(|_| parse_user_input_from(&input, today, calendar))
+}
+
+pub fn parse_user_input_from(date_string: &str, today: Type, calendar: Calendar) -> Result<Type, Error> {
+    let input = date_string.trim().to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let date = match words.as_slice() {
+        ["today"] => Some(today),
+        ["tomorrow"] => Some(today + Duration::days(1)),
+        ["yesterday"] => Some(today - Duration::days(1)),
+        [offset] if offset.starts_with(['+', '-']) => parse_offset(offset, today),
+        ["in", amount, unit] => parse_offset(&format!("+{amount}{unit}"), today),
+        ["in", offset] => parse_offset(&format!("+{offset}"), today),
+        ["next", weekday] => weekday
+            .parse::<Weekday>()
+            .ok()
+            .map(|weekday| weekday_of_next_week(today, weekday)),
+        [first, second] => parse_month_day(first, second, today)
+            .or_else(|| parse_month_day(second, first, today)),
+        [word] => word
+            .parse::<Weekday>()
+            .ok()
+            .map(|weekday| next_weekday(today, weekday))
+            .or_else(|| parse_numeric(word, today, calendar)),
+        _ => None,
+    };
+    date.ok_or(Error::ParseFailed)
+}
+
+#[inline]
+fn parse_offset(offset: &str, today: Type) -> Option<Type> {
+    let (sign, rest) = offset.split_at(1);
+    let position = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let amount: u32 = rest[..position].parse().ok()?;
+    let days = match rest[position..].trim() {
+        "" | "d" | "day" | "days" => amount as u64,
+        "w" | "week" | "weeks" => amount as u64 * 7,
+        "m" | "month" | "months" => {
+            let months = Months::new(amount);
+            return if sign == "-" {
+                today.checked_sub_months(months)
+            } else {
+                today.checked_add_months(months)
+            };
+        }
+        _ => return None,
+    };
+    let days = Days::new(days);
+    if sign == "-" {
+        today.checked_sub_days(days)
+    } else {
+        today.checked_add_days(days)
+    }
+}
+
+/// Parses `year-month-day`, `year-month` or `month-day`. Two numbers are a year and a month
+/// when the first one can't be a month.
+#[inline]
+fn parse_numeric(word: &str, today: Type, calendar: Calendar) -> Option<Type> {
+    let numbers: Vec<u32> = word
+        .split('-')
+        .map(|number| number.parse().ok())
+        .collect::<Option<_>>()?;
+    let year = |year: u32| i32::try_from(year).ok();
+    let (year, month, day) = match (numbers.as_slice(), calendar) {
+        (&[first, month, day], _) => (year(first)?, month, day),
+        (&[first, month], _) if first > 12 => (year(first)?, month, 1),
+        (&[month, day], Calendar::Gregorian) => (today.year(), month, day),
+        (&[month, day], Calendar::Jalali) => (jalali::from_gregorian(today)?.year, month, day),
+        _ => return None,
+    };
+    match calendar {
+        Calendar::Gregorian => NaiveDate::from_ymd_opt(year, month, day),
+        Calendar::Jalali => jalali::to_gregorian(year, month, day),
+    }
+}
+
+#[inline]
+fn parse_month_day(month: &str, day: &str, today: Type) -> Option<Type> {
+    let month = month.parse::<Month>().ok()?.number_from_month();
+    let day: u32 = day.parse().ok()?;
+    let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+    if date < today {
+        NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+    } else {
+        Some(date)
+    }
+}
+
+#[inline]
+fn next_weekday(today: Type, weekday: Weekday) -> Type {
+    let days = (weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64)
+        .rem_euclid(7);
+    add_days(today, days)
+}
+
+#[inline]
+fn previous_weekday(today: Type, weekday: Weekday) -> Type {
+    let days = (today.weekday().num_days_from_monday() as i64 - weekday.num_days_from_monday() as i64)
+        .rem_euclid(7);
+    add_days(today, -days)
+}
+
+/// The weekday in the week after the one of today, weeks start on monday
+#[inline]
+fn weekday_of_next_week(today: Type, weekday: Weekday) -> Type {
+    let days = 7 - today.weekday().num_days_from_monday() as i64 + weekday.num_days_from_monday() as i64;
+    add_days(today, days)
 }
 
 #[inline]
@@ -19,6 +233,11 @@ pub fn current() -> Type {
     NaiveDate::from(Local::now().naive_local())
 }
 
+#[inline]
+pub fn now() -> DateTime {
+    Local::now().naive_local()
+}
+
 #[inline]
 pub fn format(input: Option<Type>) -> String {
     match input {
@@ -27,9 +246,42 @@ pub fn format(input: Option<Type>) -> String {
     }
 }
 
-#[inline(always)]
-pub fn display(input: Option<Type>) -> String {
-    format(input)
+#[inline]
+pub fn display(input: Option<Type>, format: &DisplayFormat, calendar: Calendar) -> String {
+    match (input, format) {
+        (None, _) => String::new(),
+        (Some(date), DisplayFormat::Relative) => relative(diff_days(Some(date), Some(current()))),
+        (Some(date), DisplayFormat::Custom(format)) => display_with_format(date, format, calendar),
+        (Some(date), DisplayFormat::Default) => display_with_format(date, FORMAT, calendar),
+    }
+}
+
+/// Formats in calendar. Dates out of the range of the Jalali calendar are shown in gregorian.
+#[inline]
+fn display_with_format(date: Type, format: &str, calendar: Calendar) -> String {
+    match calendar {
+        Calendar::Jalali => jalali::format(date, format).unwrap_or_else(|_| date.format(format).to_string()),
+        Calendar::Gregorian => date.format(format).to_string(),
+    }
+}
+
+/// Short relative text for a day difference, like "today", "in 3d" or "2w ago"
+pub fn relative(days: i64) -> String {
+    let amount = days.abs();
+    let amount_str = match amount {
+        0 => return String::from("today"),
+        1 if days > 0 => return String::from("tomorrow"),
+        1 => return String::from("yesterday"),
+        2..=13 => format!("{amount}d"),
+        14..=59 => format!("{}w", amount / 7),
+        60..=729 => format!("{}mo", amount / 30),
+        _ => format!("{}y", amount / 365),
+    };
+    if days > 0 {
+        format!("in {amount_str}")
+    } else {
+        format!("{amount_str} ago")
+    }
 }
 
 #[inline]
@@ -43,3 +295,91 @@ pub fn diff_days(first: Option<Type>, next: Option<Type>) -> i64 {
 pub fn add_days(date: Type, days: i64) -> Type {
     date + Duration::days(days)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_from_monday(input: &str) -> Result<Type, Error> {
+        // 2026-10-19 is a monday
+        parse_user_input_from(input, parse("2026-10-19").unwrap(), Calendar::Gregorian)
+    }
+
+    #[test]
+    fn test_relative_dates() {
+        assert_eq!(parse_from_monday("today"), parse("2026-10-19").map_err(|_| Error::ParseFailed));
+        assert_eq!(format(parse_from_monday("Tomorrow").ok()), "2026-10-20");
+        assert_eq!(format(parse_from_monday("+3d").ok()), "2026-10-22");
+        assert_eq!(format(parse_from_monday("+2w").ok()), "2026-11-02");
+        assert_eq!(format(parse_from_monday("+1m").ok()), "2026-11-19");
+        assert_eq!(format(parse_from_monday("in 5 days").ok()), "2026-10-24");
+        assert_eq!(format(parse_from_monday("in 1w").ok()), "2026-10-26");
+    }
+
+    #[test]
+    fn test_out_of_range_dates() {
+        assert_eq!(parse_from_monday("+100000000"), Err(Error::ParseFailed));
+        assert_eq!(parse_from_monday("-100000000d"), Err(Error::ParseFailed));
+        assert_eq!(parse_from_monday("in 4294967295 weeks"), Err(Error::ParseFailed));
+        assert_eq!(parse_from_monday("4294967295-01-01"), Err(Error::ParseFailed));
+        assert_eq!(parse_from_monday("300000-01-01"), Err(Error::ParseFailed));
+    }
+
+    #[test]
+    fn test_weekdays() {
+        assert_eq!(format(parse_from_monday("fri").ok()), "2026-10-23");
+        assert_eq!(format(parse_from_monday("mon").ok()), "2026-10-19");
+        assert_eq!(format(parse_from_monday("next mon").ok()), "2026-10-26");
+        assert_eq!(format(parse_from_monday("next friday").ok()), "2026-10-30");
+        assert_eq!(format(parse_from_monday("next sun").ok()), "2026-11-01");
+    }
+
+    #[test]
+    fn test_past_dates() {
+        let parse_past = |input| parse_past_user_input_from(input, parse("2026-10-19").unwrap(), Calendar::Gregorian).ok();
+        assert_eq!(format(parse_past("friday")), "2026-10-16");
+        assert_eq!(format(parse_past("mon")), "2026-10-19");
+        assert_eq!(format(parse_past("7d")), "2026-10-12");
+        assert_eq!(format(parse_past("2026-10-01")), "2026-10-01");
+    }
+
+    #[test]
+    fn test_absolute_dates() {
+        assert_eq!(format(parse_from_monday("2026-12-24").ok()), "2026-12-24");
+        assert_eq!(format(parse_from_monday("2026-12").ok()), "2026-12-01");
+        assert_eq!(format(parse_from_monday("dec 24").ok()), "2026-12-24");
+        assert_eq!(format(parse_from_monday("24 dec").ok()), "2026-12-24");
+        assert_eq!(format(parse_from_monday("jan 1").ok()), "2027-01-01");
+        assert_eq!(format(parse_from_monday("11-02").ok()), "2026-11-02");
+        assert_eq!(parse_from_monday("someday"), Err(Error::ParseFailed));
+    }
+
+    #[test]
+    fn test_relative_display() {
+        assert_eq!(relative(0), "today");
+        assert_eq!(relative(-1), "yesterday");
+        assert_eq!(relative(3), "in 3d");
+        assert_eq!(relative(-15), "2w ago");
+        assert_eq!(relative(90), "in 3mo");
+        assert!("%Y/%m/%d".parse::<DisplayFormat>().is_ok());
+        assert!("%Q".parse::<DisplayFormat>().is_err());
+    }
+
+    #[test]
+    fn test_times() {
+        assert_eq!(parse_time("14:30").map(format_time), Ok(String::from("14:30")));
+        assert!(parse_time("25:00").is_err());
+    }
+
+    #[test]
+    fn test_jalali_input() {
+        let today = parse("2026-10-19").unwrap();
+        let parse_jalali = |input| format(parse_user_input_from(input, today, Calendar::Jalali).ok());
+        assert_eq!(parse_jalali("1405-07-27"), "2026-10-19");
+        assert_eq!(parse_jalali("08-01"), "2026-10-23");
+        assert_eq!(parse_jalali("1405-08"), "2026-10-23");
+        assert_eq!(parse_jalali("tomorrow"), "2026-10-20");
+        assert_eq!(display(Some(today), &DisplayFormat::Default, Calendar::Jalali), "1405-07-27");
+        assert_eq!(display(Some(today), &DisplayFormat::Default, Calendar::Gregorian), "2026-10-19");
+    }
+}
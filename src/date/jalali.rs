@@ -0,0 +1,231 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use chrono::{Datelike, NaiveDate, Weekday};
+// }}}
+
+// Jalali calendar conversions, based on the algorithm of jalaali-js
+const BREAKS: [i32; 20] = [
+    -61, 9, 38, 199, 426, 686, 756, 818, 1111, 1181, 1210, 1635, 2060, 2097, 2192, 2262, 2324,
+    2394, 2456, 3178,
+];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct JalaliDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+struct YearInfo {
+    gregorian_year: i32,
+    march: u32,
+    leap: i32,
+}
+
+fn year_info(year: i32) -> Option<YearInfo> {
+    if year < BREAKS[0] || year >= BREAKS[BREAKS.len() - 1] {
+        return None;
+    }
+    let gregorian_year = year + 621;
+    let mut leap_j = -14;
+    let mut jp = BREAKS[0];
+    let mut jump = 0;
+    for &jm in &BREAKS[1..] {
+        jump = jm - jp;
+        if year < jm {
+            break;
+        }
+        leap_j += jump / 33 * 8 + jump % 33 / 4;
+        jp = jm;
+    }
+    let mut n = year - jp;
+    leap_j += n / 33 * 8 + (n % 33 + 3) / 4;
+    if jump % 33 == 4 && jump - n == 4 {
+        leap_j += 1;
+    }
+    let leap_g = gregorian_year / 4 - (gregorian_year / 100 + 1) * 3 / 4 - 150;
+    let march = (20 + leap_j - leap_g) as u32;
+    if jump - n < 6 {
+        n = n - jump + (jump + 4) / 33 * 33;
+    }
+    let leap = match ((n + 1) % 33 - 1) % 4 {
+        -1 => 4,
+        leap => leap,
+    };
+    Some(YearInfo {
+        gregorian_year,
+        march,
+        leap,
+    })
+}
+
+/// First day of the Jalali year (Farvardin 1st) in the Gregorian calendar
+#[inline]
+fn new_year(year: i32) -> Option<(NaiveDate, YearInfo)> {
+    let info = year_info(year)?;
+    let date = NaiveDate::from_ymd_opt(info.gregorian_year, 3, info.march)?;
+    Some((date, info))
+}
+
+pub fn to_gregorian(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    let month_days = if month <= 6 { 31 } else { 30 };
+    if !(1..=12).contains(&month) || !(1..=month_days).contains(&day) {
+        return None;
+    }
+    let (first, _) = new_year(year)?;
+    let days = (month as i64 - 1) * 31 - (month as i64 / 7) * (month as i64 - 7) + day as i64 - 1;
+    let date = first.checked_add_signed(chrono::Duration::days(days))?;
+    // Out of range days (like 1403-07-31) would land in the next month
+    (from_gregorian(date)? == JalaliDate { year, month, day }).then_some(date)
+}
+
+pub fn from_gregorian(date: NaiveDate) -> Option<JalaliDate> {
+    let mut year = date.year() - 621;
+    let (first, info) = new_year(year)?;
+    let mut k = (date - first).num_days();
+    if k >= 0 {
+        if k <= 185 {
+            return Some(JalaliDate {
+                year,
+                month: 1 + (k / 31) as u32,
+                day: (k % 31) as u32 + 1,
+            });
+        }
+        k -= 186;
+    } else {
+        year -= 1;
+        k += 179;
+        if info.leap == 1 {
+            k += 1;
+        }
+    }
+    Some(JalaliDate {
+        year,
+        month: 7 + (k / 30) as u32,
+        day: (k % 30) as u32 + 1,
+    })
+}
+
+const MONTHS: [&str; 12] = [
+    "Farvardin", "Ordibehesht", "Khordad", "Tir", "Mordad", "Shahrivar",
+    "Mehr", "Aban", "Azar", "Dey", "Bahman", "Esfand",
+];
+
+/// Weekdays from saturday, the first day of the Jalali week
+const WEEKDAYS: [&str; 7] = ["Shanbe", "Yekshanbe", "Doshanbe", "Seshanbe", "Chaharshanbe", "Panjshanbe", "Jome"];
+
+impl JalaliDate {
+    #[inline]
+    fn day_of_year(&self) -> u32 {
+        if self.month <= 6 {
+            (self.month - 1) * 31 + self.day
+        } else {
+            186 + (self.month - 7) * 30 + self.day
+        }
+    }
+}
+
+#[inline]
+fn weekday_name(weekday: Weekday) -> &'static str {
+    WEEKDAYS[(weekday.num_days_from_sunday() as usize + 1) % 7]
+}
+
+/// Pads number like strftime, `-` doesn't pad, `_` pads with spaces and `0` with zeros
+#[inline]
+fn pad(number: i64, width: usize, flag: Option<char>, default: char) -> String {
+    match flag.unwrap_or(default) {
+        '-' => number.to_string(),
+        '_' | ' ' => format!("{number:>width$}"),
+        _ => format!("{number:0width$}"),
+    }
+}
+
+/// Formats date with a strftime format, with the year, month and day (and the names of months
+/// and weekdays) of the Jalali calendar. Specifiers that only make sense in the gregorian
+/// calendar (like week numbers) are an error.
+pub fn format(date: NaiveDate, format: &str) -> Result<String, String> {
+    let j_date = from_gregorian(date).ok_or_else(|| format!("{date} is out of the range of the Jalali calendar"))?;
+    let mut output = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        let mut spec = String::from("%");
+        let mut flag = None;
+        let specifier = loop {
+            match chars.next() {
+                Some(c @ ('-' | '_' | '0')) if flag.is_none() && spec.len() == 1 => {
+                    flag = Some(c);
+                    spec.push(c);
+                }
+                // Like %.3f or %:z, only used by time specifiers
+                Some(c @ ('.' | ':' | '1'..='9')) => spec.push(c),
+                Some(c) => {
+                    spec.push(c);
+                    break c;
+                }
+                None => return Err(format!("'{format}' ends with an incomplete specifier")),
+            }
+        };
+        let month = j_date.month as usize - 1;
+        let formatted = match specifier {
+            'Y' => j_date.year.to_string(),
+            'C' => pad(j_date.year as i64 / 100, 2, flag, '0'),
+            'y' => pad(j_date.year as i64 % 100, 2, flag, '0'),
+            'm' => pad(j_date.month as i64, 2, flag, '0'),
+            'd' => pad(j_date.day as i64, 2, flag, '0'),
+            'e' => pad(j_date.day as i64, 2, flag, ' '),
+            'j' => pad(j_date.day_of_year() as i64, 3, flag, '0'),
+            'b' | 'h' => MONTHS[month][..3].to_string(),
+            'B' => MONTHS[month].to_string(),
+            'a' => weekday_name(date.weekday())[..3].to_string(),
+            'A' => weekday_name(date.weekday()).to_string(),
+            'F' => format!("{}-{:02}-{:02}", j_date.year, j_date.month, j_date.day),
+            'D' => format!("{:02}/{:02}/{:02}", j_date.month, j_date.day, j_date.year % 100),
+            'U' | 'W' | 'V' | 'G' | 'g' | 'x' | 'c' | '+' => {
+                return Err(format!("{spec} can't be shown in the Jalali calendar"));
+            }
+            _ => date.and_hms_opt(0, 0, 0).unwrap().format(&spec).to_string(),
+        };
+        output.push_str(&formatted);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_gregorian() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+        assert_eq!(from_gregorian(date), Some(JalaliDate { year: 1403, month: 1, day: 1 }));
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        assert_eq!(from_gregorian(date), Some(JalaliDate { year: 1405, month: 7, day: 27 }));
+        let date = NaiveDate::from_ymd_opt(2025, 3, 20).unwrap();
+        assert_eq!(from_gregorian(date), Some(JalaliDate { year: 1403, month: 12, day: 30 }));
+    }
+
+    #[test]
+    fn test_format() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        assert_eq!(format(date, "%a %d %b"), Ok(String::from("Dos 27 Meh")));
+        assert_eq!(format(date, "%A %-d %B %Y, %j %%"), Ok(String::from("Doshanbe 27 Mehr 1405, 213 %")));
+        assert_eq!(format(date, "%F %e %H:%M"), Ok(String::from("1405-07-27 27 00:00")));
+        assert!(format(date, "week %W").is_err());
+    }
+
+    #[test]
+    fn test_to_gregorian() {
+        assert_eq!(to_gregorian(1403, 1, 1), NaiveDate::from_ymd_opt(2024, 3, 20));
+        assert_eq!(to_gregorian(1405, 7, 27), NaiveDate::from_ymd_opt(2026, 10, 19));
+        assert_eq!(to_gregorian(1403, 12, 30), NaiveDate::from_ymd_opt(2025, 3, 20));
+        assert_eq!(to_gregorian(1404, 12, 30), None);
+        assert_eq!(to_gregorian(1403, 7, 31), None);
+        assert_eq!(to_gregorian(1405, 1, 100000000), None);
+        assert_eq!(to_gregorian(1405, 13, 1), None);
+        assert_eq!(to_gregorian(i32::MAX, 1, 1), None);
+    }
+}
@@ -8,6 +8,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use std::env;
 use std::process::Command;
+use std::sync::RwLock;
 // }}}
 
 #[inline(always)]
@@ -15,6 +16,12 @@ pub fn append_notes_to_path_parent(filename: &Path) -> PathBuf {
     filename.parent().unwrap().join("notes")
 }
 
+/// Archived todos are kept next to the todo file, sharing its notes directory
+#[inline(always)]
+pub fn archive_path(filename: &Path) -> PathBuf {
+    filename.parent().unwrap().join("archive")
+}
+
 #[inline(always)]
 pub fn open_temp_editor(content: Option<&str>, path: PathBuf) -> io::Result<String> {
     let mut file = File::create(&path)?;
@@ -45,12 +52,58 @@ pub fn append_home_dir(vec: [&str; 4]) -> PathBuf {
     path
 }
 
+/// `$XDG_CONFIG_HOME/c3`, or `~/.config/c3` if it's not set
 #[inline(always)]
-pub fn get_todo_path() -> io::Result<PathBuf> {
-    let file = append_home_dir([".local", "share", "calcurse", "todo"]);
-    if file.is_dir() {
-        remove_dir(&file)?;
+pub fn config_dir() -> PathBuf {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(config_home) if !config_home.is_empty() => PathBuf::from(config_home).join("c3"),
+        _ => home_dir().unwrap().join(".config").join("c3"),
+    }
+}
+
+/// `$XDG_DATA_HOME/c3`, or `~/.local/share/c3` if it's not set
+#[inline(always)]
+pub fn data_dir() -> PathBuf {
+    match env::var_os("XDG_DATA_HOME") {
+        Some(data_home) if !data_home.is_empty() => PathBuf::from(data_home).join("c3"),
+        _ => home_dir().unwrap().join(".local").join("share").join("c3"),
+    }
+}
+
+static DEFAULT_TODO_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the todo path that is used when none is given, like the one of the config file
+#[inline]
+pub fn set_default_todo_path(path: Option<PathBuf>) {
+    if let Ok(mut default_todo_path) = DEFAULT_TODO_PATH.write() {
+        *default_todo_path = path;
+    }
+}
+
+/// Replaces a leading `~` with the home directory
+#[inline]
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            home_dir().unwrap().join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
     }
+}
+
+#[inline(always)]
+pub fn get_todo_path() -> io::Result<PathBuf> {
+    let default_todo_path = DEFAULT_TODO_PATH.read().ok().and_then(|path| path.clone());
+    let file = match default_todo_path {
+        Some(file) => file,
+        None => {
+            let file = append_home_dir([".local", "share", "calcurse", "todo"]);
+            if file.is_dir() {
+                remove_dir(&file)?;
+            }
+            file
+        }
+    };
     let parentdir = file.parent().unwrap();
     std::fs::create_dir_all(parentdir)?;
     Ok(file)
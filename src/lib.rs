@@ -1,11 +1,13 @@
 // vim:fileencoding=utf-8:foldmethod=marker
 // imports {{{
 use clap::{Parser, ValueEnum};
+use date::{Calendar, DisplayFormat};
 use fileio::get_todo_path;
 use std::fmt;
 use std::path::PathBuf;
-use todo_app::SortMethod;
+use todo_app::{DonePropagation, SortMethod};
 
+pub mod config;
 pub mod date;
 pub mod fileio;
 pub mod todo_app;
@@ -19,6 +21,18 @@ pub enum DoOnSelected {
     Done,
 }
 
+#[derive(ValueEnum, Clone, Debug, Default, PartialEq)]
+pub enum ProgressFormat {
+    None,
+    /// Like [3/7]
+    #[default]
+    Count,
+    /// Like [42%]
+    Percent,
+    /// Like [####......]
+    Bar,
+}
+
 #[derive(Parser, Debug, Default)]
 #[command(author, version, about, long_about = None)]
 pub struct AppArgs {
@@ -33,9 +47,34 @@ pub struct AppArgs {
     #[arg(default_value=get_todo_path().unwrap().into_os_string())]
     pub todo_path: PathBuf,
 
+    /// Open the archive of the todo file, read only
+    #[arg(long)]
+    pub archive: bool,
+
     /// Sort method, how sortings are done in the app
     #[arg(long, default_value = "normal")]
     pub sort_method: SortMethod,
+
+    /// Attribute to sort by when the sort method is attribute
+    #[arg(long, default_value = "estimate")]
+    pub sort_attribute: String,
+
+    /// How marking todos done affects their parents and dependencies
+    #[arg(long, default_value = "parents")]
+    pub done_propagation: DonePropagation,
+
+    /// Record the day new todos are created and done todos are completed in their created and
+    /// completed attributes
+    #[arg(long)]
+    pub record_created: bool,
+}
+
+impl AppArgs {
+    /// Checks the options that clap can't check one by one
+    #[inline]
+    pub fn check(&self) -> Result<(), String> {
+        self.display_args.date_format.check_calendar(self.display_args.calendar)
+    }
 }
 
 #[derive(Parser, Debug, Default)]
@@ -52,6 +91,38 @@ pub struct DisplayArgs {
     /// String before undone todos
     #[arg(long, default_value_t=String::from("[ ] "))]
     undone_string: String,
+
+    /// Hide todos that are blocked by undone todos
+    #[arg(long, default_value_t = false)]
+    hide_blocked: bool,
+
+    /// How done and total counts of todo lists (and their dependencies) are shown
+    #[arg(long, default_value = "count")]
+    progress: ProgressFormat,
+
+    /// Show when todos were created and completed
+    #[arg(long, default_value_t = false)]
+    show_timestamps: bool,
+
+    /// How dates are displayed, a strftime format (like "%a %d %b") or "relative"
+    #[arg(long, default_value = "default")]
+    date_format: DisplayFormat,
+
+    /// Calendar to show and enter dates in. Files are always written in gregorian
+    #[arg(long, default_value = "gregorian")]
+    calendar: Calendar,
+}
+
+impl DisplayArgs {
+    #[inline]
+    pub fn date_format(&self) -> &DisplayFormat {
+        &self.date_format
+    }
+
+    #[inline]
+    pub fn calendar(&self) -> Calendar {
+        self.calendar
+    }
 }
 
 pub trait TodoDisplay: fmt::Display {
@@ -1,17 +1,17 @@
 // vim:fileencoding=utf-8:foldmethod=marker
 // imports {{{
-use clap::Parser;
+use clap::{error::ErrorKind, ArgMatches, CommandFactory, FromArgMatches, Parser};
 use std::io;
 pub(crate) mod cli_app;
 pub(crate) mod tui_app;
-use c3::{todo_app::App, AppArgs};
+use c3::{config::Config, fileio, todo_app::App, AppArgs};
 use cli_app::CliArgs;
 use tui_app::TuiArgs;
 // }}}
 
 /// A tree-like todo application that makes you smile
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_override_self = true)]
 pub struct Args {
     #[command(flatten)]
     pub app_args: AppArgs,
@@ -23,12 +23,34 @@ pub struct Args {
     pub tui_args: TuiArgs,
 }
 
+impl Args {
+    /// Updates the options that config_matches has, see Config::matches
+    fn update_from_config(&mut self, config_matches: &ArgMatches) -> Result<(), clap::Error> {
+        self.app_args.update_from_arg_matches(config_matches)?;
+        self.tui_args.update_from_arg_matches(config_matches)?;
+        self.cli_args.update_from_config(config_matches)
+    }
+}
+
 fn main() -> io::Result<()> {
-    let args = Args::parse();
+    let config = Config::read(&Config::default_path())?;
+    fileio::set_default_todo_path(config.todo_path());
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    // The config fills the options that the command line doesn't set
+    if let Err(error) = config
+        .matches(Args::command(), &matches, &args.app_args.todo_path)
+        .and_then(|config_matches| args.update_from_config(&config_matches))
+    {
+        error.exit();
+    }
+    if let Err(error) = args.app_args.check() {
+        Args::command().error(ErrorKind::ValueValidation, error).exit();
+    }
     let mut app = App::new(args.app_args);
 
     if cli_app::run(&mut app, args.cli_args).is_err() {
-        let result = tui_app::run(&mut app, args.tui_args);
+        let result = tui_app::run(&mut app, args.tui_args, &config);
         tui_app::shutdown()?;
         result
     } else {
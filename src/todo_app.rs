@@ -1,18 +1,35 @@
 // vim:fileencoding=utf-8:foldmethod=marker
 // imports {{{
 use std::cmp;
-use std::fs::create_dir_all;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, create_dir_all};
 use std::path::Path;
 use std::str::{FromStr, Lines};
 use std::{io, path::PathBuf};
 use clap::ValueEnum;
 pub use todo::schedule::Schedule;
+pub use todo::history::Stats;
+pub use todo::attributes::Attributes;
+use todo::attributes::compare_values;
+pub mod links;
+pub mod register;
+pub mod template;
+pub mod text;
 mod todo;
 mod todo_list;
-use crate::{fileio, AppArgs};
+mod outline;
+mod selection;
+mod undo;
+pub use outline::OutlineRow;
+use outline::{Folds, OutlineCache};
+use selection::Selection;
+use undo::{SavedList, Snapshot, UndoHistory};
+use crate::{date, fileio, AppArgs};
 use std::rc::Rc;
-pub use todo::Todo;
-pub use self::todo_list::TodoList;
+pub use todo::{is_tag, Todo};
+pub use self::todo_list::{Borrowed, BorrowedMut, TodoCmp, TodoList};
+use todo::edit_list;
 // }}}
 
 #[derive(ValueEnum, Clone, Debug, PartialEq, Default)]
@@ -24,12 +41,15 @@ pub enum SortMethod {
     AbandonedFirst,
     #[value(alias = "nta")]
     NormalThenAbandoned,
+    /// By the value of the attribute given with --sort-attribute, todos without it go last
+    #[value(alias = "attr")]
+    Attribute,
 }
 
 impl SortMethod {
-    pub fn cmp_function(&self) -> fn(&Todo, &Todo) -> cmp::Ordering {
+    pub fn cmp_function(&self) -> TodoCmp {
         match self {
-            Self::AbandonedFirst => |a: &Todo, b: &Todo| {
+            Self::AbandonedFirst => |a: &Todo, b: &Todo, _: &str| {
                 let order = b
                     .abandonment_coefficient()
                     .total_cmp(&a.abandonment_coefficient());
@@ -39,8 +59,8 @@ impl SortMethod {
                     order
                 }
             },
-            Self::Normal => |a: &Todo, b: &Todo| a.cmp(b),
-            Self::NormalThenAbandoned => |a: &Todo, b: &Todo| {
+            Self::Normal => |a: &Todo, b: &Todo, _: &str| a.cmp(b),
+            Self::NormalThenAbandoned => |a: &Todo, b: &Todo, _: &str| {
                 let order = a.cmp(b);
                 if order.is_eq() {
                     b.abandonment_coefficient()
@@ -48,21 +68,70 @@ impl SortMethod {
                 } else {
                     order
                 }
-            }
+            },
+            Self::Attribute => |a: &Todo, b: &Todo, key: &str| {
+                let order = match (a.attribute(key), b.attribute(key)) {
+                    (Some(a_value), Some(b_value)) => compare_values(a_value, b_value),
+                    (Some(_), None) => cmp::Ordering::Less,
+                    (None, Some(_)) => cmp::Ordering::Greater,
+                    (None, None) => cmp::Ordering::Equal,
+                };
+                a.done().cmp(&b.done()).then(order).then_with(|| a.cmp(b))
+            },
         }
     }
 }
 
+/// How marking a todo done or undone affects its parents and its dependencies
+#[derive(ValueEnum, Clone, Debug, PartialEq, Default)]
+pub enum DonePropagation {
+    /// Todos are marked on their own
+    None,
+    /// Parents are done when their last undone todo is done, and undone with it
+    #[default]
+    Parents,
+    /// Todos of the dependency list are done and undone with their parent. Their done todos
+    /// are undone when a scheduled parent recurs
+    Children,
+    /// Both parents and children
+    Both,
+}
+
+impl DonePropagation {
+    #[inline]
+    pub fn to_parents(&self) -> bool {
+        matches!(self, Self::Parents | Self::Both)
+    }
+
+    #[inline]
+    pub fn to_children(&self) -> bool {
+        matches!(self, Self::Children | Self::Both)
+    }
+}
+
 pub type Restriction = Rc<dyn Fn(&Todo) -> bool>;
+/// A list of the tree, None is the root list
+type ListCell = Option<Rc<RefCell<TodoList>>>;
+/// The todo tree with the position in it. Changes are only recorded for undo after
+/// begin_undo_step (the TUI begins a step for every action), so callers that don't call it
+/// can't undo them, nor direct changes of todo_list.
 pub struct App {
     notes_dir: PathBuf,
     pub todo_list: TodoList,
     pub index: usize,
     changed: bool,
-    pub tree_path: Vec<usize>,
+    tree_path: Vec<usize>,
+    /// The list of tree_path, see update_current_list
+    current: ListCell,
     pub args: AppArgs,
     pub removed_todos: Vec<Todo>,
     restriction: Restriction,
+    /// Undone ids of the tree that the hide blocked restriction reads, see update_blockers
+    undone_ids: Rc<RefCell<HashSet<String>>>,
+    undo_history: UndoHistory,
+    selection: Selection,
+    folds: Folds,
+    outline: RefCell<OutlineCache>,
 }
 
 #[derive(Debug)]
@@ -110,7 +179,12 @@ fn first_word_parse<T: FromStr>(input: &str) -> (Option<T>, String) {
 
 impl App {
     #[inline]
-    pub fn new(args: AppArgs) -> Self {
+    pub fn new(mut args: AppArgs) -> Self {
+        if args.archive {
+            // Everything in the archive is done
+            args.todo_path = fileio::archive_path(&args.todo_path);
+            args.display_args.show_done = true;
+        }
         let notes_dir = fileio::append_notes_to_path_parent(&args.todo_path);
         let todo_list = Self::read_a_todo_list(&args.todo_path, &notes_dir, &args);
         let mut app = App {
@@ -119,9 +193,15 @@ impl App {
             todo_list,
             index: 0,
             tree_path: vec![],
+            current: None,
             changed: false,
             args,
             restriction: Self::no_restriction(),
+            undone_ids: Rc::default(),
+            undo_history: UndoHistory::default(),
+            selection: Selection::default(),
+            folds: Folds::default(),
+            outline: RefCell::default(),
         };
         app.update_show_done_restriction();
         app
@@ -130,16 +210,95 @@ impl App {
     #[inline(always)]
     fn read_a_todo_list(path: &Path, notes_dir: &Path, args: &AppArgs) -> TodoList {
         let mut todo_list = TodoList::read(path);
+        todo_list.count_changes();
 
-        todo_list.set_todo_cmp(args.sort_method.cmp_function());
+        todo_list.set_todo_cmp(args.sort_method.cmp_function(), args.sort_attribute.as_str().into());
         todo_list.sort();
         todo_list.changed = false;
         if !args.no_tree {
             let _ = todo_list.read_dependencies(notes_dir);
+            todo_list.mark_shared_dependencies(&todo_list.dependency_refs());
+            todo_list.reset_recurred_dependencies(args.done_propagation.to_children());
+        } else {
+            todo_list.read_histories(notes_dir);
         }
         todo_list
     }
 
+    /// Marks dependencies that more than one todo reference as shared
+    #[inline]
+    pub fn update_shared_dependencies(&mut self) {
+        let refs = self.todo_list.dependency_refs();
+        self.todo_list.mark_shared_dependencies(&refs);
+    }
+
+    #[inline]
+    fn borrow_list<'a>(&'a self, list: &'a ListCell) -> Borrowed<'a, TodoList> {
+        match list {
+            Some(list) => Borrowed::Cell(list.borrow()),
+            None => Borrowed::Plain(&self.todo_list),
+        }
+    }
+
+    #[inline]
+    fn borrow_list_mut<'a>(&'a mut self, list: &'a ListCell) -> BorrowedMut<'a, TodoList> {
+        match list {
+            Some(list) => BorrowedMut::Cell(edit_list(list)),
+            None => {
+                self.todo_list.count_change();
+                BorrowedMut::Plain(&mut self.todo_list)
+            }
+        }
+    }
+
+    /// Walks path from the root list while its todos have todo lists, calling f with each
+    /// todo on the way. Returns the list it got to and how many todos of path it went into.
+    fn walk_path(&self, path: &[usize], mut f: impl FnMut(&Todo)) -> (ListCell, usize) {
+        let mut list: ListCell = None;
+        let mut depth = 0;
+        for &index in path {
+            let next = {
+                let current = self.borrow_list(&list);
+                let Some(todo) = current.todos.get(index) else {
+                    break;
+                };
+                f(todo);
+                match todo.dependency.as_ref().filter(|dep| dep.is_list()) {
+                    Some(dependency) => dependency.shared_list().clone(),
+                    None => break,
+                }
+            };
+            list = Some(next);
+            depth += 1;
+        }
+        (list, depth)
+    }
+
+    /// Finds the list of the tree path again, after the path or the tree changes
+    #[inline]
+    fn update_current_list(&mut self) {
+        self.current = self.walk_path(&self.tree_path, |_| {}).0;
+    }
+
+    #[inline]
+    fn set_tree_path(&mut self, tree_path: Vec<usize>) {
+        self.tree_path = tree_path;
+        self.update_current_list();
+    }
+
+    /// Indices of the lists of the current list and its parents, from the root list
+    #[inline]
+    pub fn tree_path(&self) -> &[usize] {
+        &self.tree_path
+    }
+
+    /// Goes to the list at tree_path and to index of it, unlike go_to the index isn't checked
+    #[inline]
+    pub fn set_tree_position(&mut self, tree_path: Vec<usize>, index: usize) {
+        self.set_tree_path(tree_path);
+        self.index = index;
+    }
+
     #[inline]
     pub fn append_list_from_path(&mut self, path: &Path) {
         let notes_dir = fileio::append_notes_to_path_parent(path);
@@ -156,13 +315,49 @@ impl App {
     pub fn open_path(&mut self, path: PathBuf) {
         self.notes_dir = fileio::append_notes_to_path_parent(&path);
         self.todo_list = Self::read_a_todo_list(&path, &self.notes_dir, &self.args);
-        self.tree_path = vec![];
+        self.set_tree_path(vec![]);
         self.args.todo_path = path;
     }
 
+    /// Moves the archivable todos of the current list and its dependencies to the archive, and
+    /// writes both of them. Todos of nested lists are archived in copies of their parents, so
+    /// the archive keeps the path to them. Returns how many todos were archived.
+    #[inline]
+    pub fn archive_done(&mut self, before: Option<date::Type>) -> io::Result<usize> {
+        if self.is_read_only() {
+            return Ok(0);
+        }
+        let path = fileio::archive_path(&self.args.todo_path);
+        if path == self.args.todo_path {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} would be its own archive", path.display())));
+        }
+        let mut archive = Self::read_a_todo_list(&path, &self.notes_dir, &self.args);
+        let mut names = self.todo_list.file_names();
+        names.extend(archive.file_names());
+        let (mut taken, count) = self.current_list_mut().take_archivable(before, &mut names);
+        if taken.is_empty() {
+            return Ok(0);
+        }
+        self.fix_index();
+        for parent in self.parents().iter().rev() {
+            taken = vec![parent.parent_copy(taken, &mut names)];
+        }
+        archive.todos.append(&mut taken);
+        archive.sort();
+        Self::write_list_to_path(&archive, &path)?;
+        self.write()?;
+        // The archive is already written, undoing would bring back a copy of its todos
+        self.undo_history.clear();
+        Ok(count)
+    }
+
     #[inline]
     pub fn output_list_to_path(&self, path: &Path) -> io::Result<()> {
-        let list = self.current_list();
+        Self::write_list_to_path(&self.current_list(), path)
+    }
+
+    #[inline]
+    fn write_list_to_path(list: &TodoList, path: &Path) -> io::Result<()> {
         let dependency_path = fileio::append_notes_to_path_parent(path);
         create_dir_all(&dependency_path)?;
         list.force_write(path)?;
@@ -171,6 +366,150 @@ impl App {
         Ok(())
     }
 
+    /// Saves the current todo with its whole dependency tree as a template
+    pub fn save_current_as_template(&self, name: &str) -> io::Result<()> {
+        let Some(todo) = self.todo() else {
+            return Ok(());
+        };
+        let mut list = TodoList::new();
+        list.push(todo.clone());
+        self.write_template(name, &list)
+    }
+
+    #[inline]
+    pub fn write_template(&self, name: &str, list: &TodoList) -> io::Result<()> {
+        Self::write_list_to_path(list, &template::path(&self.args.todo_path, name))
+    }
+
+    /// Appends a fresh copy of the template to the current list. `message` replaces the message
+    /// of the template's todo, placeholders are filled in messages and notes.
+    pub fn append_template(&mut self, name: &str, message: Option<&str>, vars: &[(String, String)]) -> bool {
+        let path = template::path(&self.args.todo_path, name);
+        if !path.is_file() {
+            return false;
+        }
+        let notes_dir = fileio::append_notes_to_path_parent(&path);
+        let mut todo_list = Self::read_a_todo_list(&path, &notes_dir, &self.args);
+        if let Some(message) = message {
+            for todo in todo_list.todos.iter_mut() {
+                todo.message = message.to_string();
+            }
+        }
+        let mut names = self.todo_list.file_names();
+        todo_list.instantiate(vars, self.args.record_created, &mut names);
+        self.append_list(todo_list);
+        self.current_list_mut().sort();
+        true
+    }
+
+    /// Writes copies of todos with their dependencies to a register of dir (see
+    /// [`register::dir`]), replacing what it had. Fails with InvalidInput if name isn't a valid
+    /// register name (see [`register::is_valid_name`]).
+    pub fn write_register(&self, dir: &Path, name: &str, todos: &[Todo]) -> io::Result<()> {
+        if !register::is_valid_name(name) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid register name '{name}'")));
+        }
+        let path = register::path(dir, name);
+        if let Some(dir) = path.parent().filter(|dir| dir.is_dir()) {
+            fs::remove_dir_all(dir)?;
+        }
+        let mut list = TodoList::new();
+        for todo in todos {
+            list.push(todo.clone());
+        }
+        Self::write_list_to_path(&list, &path)
+    }
+
+    /// Reads the todos of a register of dir, an invalid register name has none
+    #[inline]
+    pub fn read_register(&self, dir: &Path, name: &str) -> TodoList {
+        if !register::is_valid_name(name) {
+            return TodoList::new();
+        }
+        let path = register::path(dir, name);
+        Self::read_a_todo_list(&path, &fileio::append_notes_to_path_parent(&path), &self.args)
+    }
+
+    /// Adds copies of the todos of a register to the current list. Their dependencies get new
+    /// files, so they don't share them with the yanked todos. Returns whether it had todos.
+    #[inline]
+    pub fn paste_register(&mut self, dir: &Path, name: &str) -> bool {
+        let register = self.read_register(dir, name);
+        self.paste_todos(register.todos, false)
+    }
+
+    /// Adds the todos of a register to the current list like paste_register, but the ones whose
+    /// todo list is in this tree share it instead of getting a copy. Returns whether it had todos.
+    #[inline]
+    pub fn paste_register_as_link(&mut self, dir: &Path, name: &str) -> bool {
+        let register = self.read_register(dir, name);
+        self.paste_todos(register.todos, true)
+    }
+
+    /// The todo list of the tree with the file name
+    #[inline]
+    fn tree_list(&self, name: &str) -> Option<Rc<RefCell<TodoList>>> {
+        self.todo_list.tree_find_map(&mut |todo| {
+            todo.dependency
+                .as_ref()
+                .filter(|dependency| dependency.is_list() && dependency.name() == name)
+                .map(|dependency| dependency.shared_list().clone())
+        })
+    }
+
+    /// Whether list is the current list or a list above it, linking it into the current list
+    /// would make a cycle
+    #[inline]
+    fn holds_current_list(&self, list: &Rc<RefCell<TodoList>>) -> bool {
+        let Some(current) = self.current.as_ref() else {
+            return false;
+        };
+        Rc::ptr_eq(list, current) || list.borrow().tree_find_map(&mut |todo| {
+            todo.dependency
+                .as_ref()
+                .filter(|dependency| dependency.is_list() && Rc::ptr_eq(dependency.shared_list(), current))
+                .map(|_| ())
+        }).is_some()
+    }
+
+    /// Adds the todos of text (see [`text::parse`]) to the current list, like pasting them from
+    /// the system clipboard. Returns whether it had todos.
+    #[inline]
+    pub fn paste_text(&mut self, text: &str) -> bool {
+        self.paste_todos(text::parse(text), false)
+    }
+
+    /// Adds todos to the current list with new file names, the ones that link and whose list is
+    /// in the tree share it instead (unless the current list is or is below it)
+    fn paste_todos(&mut self, mut todos: Vec<Todo>, link: bool) -> bool {
+        if todos.is_empty() {
+            return false;
+        }
+        let mut names = self.todo_list.file_names();
+        for todo in todos.iter_mut() {
+            let name = todo.dependency.as_ref().filter(|_| link).map(|dependency| dependency.name().to_string());
+            match name.and_then(|name| self.tree_list(&name)).filter(|list| !self.holds_current_list(list)) {
+                Some(list) => todo.link_dependency(list, &mut names),
+                None => todo.renew_file_names(&mut names),
+            }
+        }
+        let record_created = self.args.record_created;
+        let mut list = self.current_list_mut();
+        let mut index = 0;
+        for mut todo in todos {
+            if record_created {
+                todo.ensure_created();
+            }
+            list.push(todo);
+            index = list.reorder_last();
+        }
+        drop(list);
+        self.index = index;
+        self.fix_index();
+        self.update_shared_dependencies();
+        true
+    }
+
     #[inline]
     pub fn append_list(&mut self, todo_list: TodoList) {
         self.current_list_mut().append_list(todo_list)
@@ -200,7 +539,8 @@ impl App {
 
     #[inline(always)]
     fn batch_edit_current_list(&mut self, messages: Lines<'_>) {
-        let todolist = self.current_list_mut();
+        let record_created = self.args.record_created;
+        let mut todolist = self.current_list_mut();
         let mut delete_indices: Vec<usize> = vec![];
         let mut changed = false;
         let mut lines: Vec<IndexedLine> = messages
@@ -227,7 +567,11 @@ impl App {
                 }
                 last_index = index+1;
             } else {
-                todolist.push(Todo::new(line.message, line.priority));
+                let mut todo = Todo::new(line.message, line.priority);
+                if record_created {
+                    todo.ensure_created();
+                }
+                todolist.push(todo);
             }
         }
         todolist.retrain_indices(delete_indices);
@@ -235,7 +579,9 @@ impl App {
         if todolist.changed {
             todolist.sort();
         }
-        self.changed = todolist.changed;
+        let changed = todolist.changed;
+        drop(todolist);
+        self.changed = changed;
     }
 
     #[inline]
@@ -245,28 +591,49 @@ impl App {
 
     #[inline]
     pub fn is_changed(&self) -> bool {
-        self.changed
+        self.changed && !self.is_read_only()
+    }
+
+    /// The archive is opened read only, it's only changed by archiving todos
+    #[inline]
+    pub fn is_read_only(&self) -> bool {
+        self.args.archive
     }
 
     #[inline(always)]
     pub fn increase_day_by(&mut self, days: i64) {
-        if let Some(Some(schedule)) = self.todo_mut().map(|todo| todo.schedule.as_mut()) {
-            schedule.add_days_to_date(-1*days);
-            self.reorder_current();
+        self.edit_selected(|todo| {
+            if let Some(schedule) = todo.schedule.as_mut() {
+                schedule.add_days_to_date(-1*days);
+            }
+        })
+    }
+
+    /// A new todo, created today when `--record-created` is given
+    #[inline]
+    pub fn new_todo(&self, message: String, priority: u8) -> Todo {
+        let mut todo = Todo::new(message, priority);
+        if self.args.record_created {
+            todo.ensure_created();
         }
+        todo
     }
 
     #[inline]
     pub fn prepend(&mut self, message: String) {
-        self.current_list_mut().prepend(Todo::new(message, 1));
+        let todo = self.new_todo(message, 1);
+        self.current_list_mut().prepend(todo);
         self.index = 0;
     }
 
     #[inline]
     pub fn append(&mut self, message: String) {
-        let todo_list = self.current_list_mut();
-        todo_list.push(Todo::new(message, 0));
-        self.index = todo_list.reorder_last();
+        let todo = self.new_todo(message, 0);
+        let mut todo_list = self.current_list_mut();
+        todo_list.push(todo);
+        let index = todo_list.reorder_last();
+        drop(todo_list);
+        self.index = index;
     }
 
     pub fn index(&self) -> usize {
@@ -279,47 +646,152 @@ impl App {
         self.update_show_done_restriction();
     }
 
+    #[inline]
+    pub fn toggle_hide_blocked(&mut self) {
+        self.args.display_args.hide_blocked = !self.args.display_args.hide_blocked;
+        self.update_show_done_restriction();
+    }
+
     pub fn update_show_done_restriction(&mut self) {
         if self.args.display_args.show_done {
             self.unset_restriction()
         } else {
             self.set_restriction(Rc::new(|todo| !todo.done()))
         }
+        if self.args.display_args.hide_blocked {
+            self.update_blockers();
+            let undone_ids = self.undone_ids.clone();
+            self.set_restriction_with_last(Rc::new(move |todo| !todo.is_blocked(&undone_ids.borrow())), None)
+        }
     }
 
-    fn max_tree_length(&self) -> usize {
-        let mut current_list = &self.todo_list;
-        let mut max_i = 0;
-        for &index in self.tree_path.iter() {
-            if let Some(dependency) = current_list
-                .todos
-                .get(index)
-                .and_then(|todo| todo.dependency.as_ref())
-            {
-                current_list = &dependency.todo_list;
-                max_i += 1;
-            } else {
-                break;
-            }
+    /// Refreshes the undone ids that decide which todos are blocked when they're hidden, it
+    /// should be called after the tree changes
+    #[inline]
+    pub fn update_blockers(&self) {
+        if self.args.display_args.hide_blocked {
+            *self.undone_ids.borrow_mut() = self.todo_list.undone_ids();
+        }
+    }
+
+    /// Makes the current todo blocked by the first todo of the whole tree that matches query
+    pub fn block_current_by(&mut self, query: &str) -> bool {
+        let Some(current_hash) = self.todo().map(|todo| todo.hash()) else {
+            return false;
+        };
+        let Some(position) = self
+            .todo_list
+            .tree_position(&|_, todo| todo.hash() != current_hash && todo.matches(query))
+        else {
+            return false;
+        };
+        let (&index, tree_path) = position.split_last().unwrap();
+        let list = self.walk_path(tree_path, |_| {}).0;
+        self.record_undo(&list);
+        let id = self.borrow_list_mut(&list).todos[index].ensure_id();
+        self.changed = true;
+        if self.todo_mut().map(|mut todo| todo.block_by(&id)).is_some() {
+            self.update_blockers();
+            return true;
         }
-        max_i
+        false
+    }
+
+    #[inline]
+    fn max_tree_length(&self) -> usize {
+        self.walk_path(&self.tree_path, |_| {}).1
     }
 
     #[inline]
     pub fn toggle_current_done(&mut self) {
-        self.todo_mut().unwrap().toggle_done();
+        if !self.selection.is_empty() {
+            return self.toggle_selected_done();
+        }
+        let propagation = self.args.done_propagation.clone();
+        let record_completed = self.args.record_created;
+        if propagation.to_children() {
+            let positions = self.moving_positions();
+            self.record_undo_of_dependencies(&positions);
+        }
+        let Some(mut todo) = self.todo_mut() else {
+            return;
+        };
+        todo.toggle_done();
+        let done = todo.done();
+        if done && record_completed {
+            todo.ensure_completed();
+        }
+        if propagation.to_children() {
+            todo.set_dependencies_done(done, record_completed);
+        }
+        drop(todo);
+        self.update_blockers();
         self.reorder_current();
-        while self.is_undone_empty() && self.traverse_up() {
+        if !propagation.to_parents() {
+            return;
+        }
+        if !done {
+            self.undo_ancestors();
+        } else if self.is_undone_empty() && self.traverse_up() && self.todo().is_some_and(|todo| !todo.done()) {
+            self.toggle_current_done()
+        }
+    }
+
+    fn toggle_selected_done(&mut self) {
+        let propagation = self.args.done_propagation.clone();
+        let record_completed = self.args.record_created;
+        let mut any_undone = false;
+        if propagation.to_children() {
+            let positions = self.moving_positions();
+            self.record_undo_of_dependencies(&positions);
+        }
+        self.edit_selected(|todo| {
+            todo.toggle_done();
+            let done = todo.done();
+            any_undone |= !done;
+            if done && record_completed {
+                todo.ensure_completed();
+            }
+            if propagation.to_children() {
+                todo.set_dependencies_done(done, record_completed);
+            }
+        });
+        self.update_blockers();
+        if !propagation.to_parents() {
+            return;
+        }
+        if any_undone {
+            self.undo_ancestors();
+        } else if self.is_undone_empty() && self.traverse_up() && self.todo().is_some_and(|todo| !todo.done()) {
             self.toggle_current_done()
         }
     }
 
+    /// Marks done todos of the current path undone, without leaving the current list
+    fn undo_ancestors(&mut self) {
+        for depth in (0..self.tree_path.len()).rev() {
+            let index = self.tree_path[depth];
+            let list = self.walk_path(&self.tree_path[..depth], |_| {}).0;
+            if !self.borrow_list(&list).todos[index].done() {
+                break;
+            }
+            self.record_undo(&list);
+            let mut list = self.borrow_list_mut(&list);
+            list.todos[index].set_done(false);
+            list.changed = true;
+        }
+    }
+
     #[inline]
     pub fn read(&mut self) {
+        // The lists of the snapshots aren't in the tree that's read
+        self.undo_history.clear();
         self.changed = false;
         self.todo_list = Self::read_a_todo_list(&self.args.todo_path, &self.notes_dir, &self.args);
+        self.update_blockers();
         let len = self.max_tree_length();
         self.tree_path.truncate(len);
+        self.update_current_list();
         self.fix_index();
     }
 
@@ -341,21 +813,9 @@ impl App {
     }
 
     #[inline]
-    pub fn parent(&mut self) -> Option<&Todo> {
-        let mut list = &self.todo_list;
+    pub fn parent(&self) -> Option<Todo> {
         let mut parent = None;
-        for &index in &self.tree_path {
-            parent = Some(&list.todos[index]);
-            if let Some(todo_list) = list.todos[index]
-                .dependency
-                .as_ref()
-                .and_then(|dep| dep.todo_list())
-            {
-                list = todo_list
-            } else {
-                break;
-            }
-        }
+        self.walk_path(&self.tree_path, |todo| parent = Some(todo.clone()));
         parent
     }
 
@@ -380,26 +840,24 @@ impl App {
 
     #[inline]
     pub fn traverse_down(&mut self) {
-        if self.is_tree() {
-            match self.todo() {
-                Some(todo) if todo.dependency.as_ref().map_or(false, |dep| dep.is_list()) => {
-                    let index = self.index;
-                    let restriction = self.restriction.clone();
-                    let true_index = self
-                        .current_list()
-                        .true_position_in_list(index, &restriction);
-                    self.tree_path.push(true_index);
-                    self.index = 0;
-                    self.update_show_done_restriction();
-                }
-                _ => {}
-            }
+        let has_list = self.todo().map_or(false, |todo| todo.dependency.as_ref().map_or(false, |dep| dep.is_list()));
+        if self.is_tree() && has_list {
+            let index = self.index;
+            let restriction = self.restriction.clone();
+            let true_index = self
+                .current_list()
+                .true_position_in_list(index, &restriction);
+            self.tree_path.push(true_index);
+            self.update_current_list();
+            self.index = 0;
+            self.update_show_done_restriction();
         }
     }
 
     #[inline]
     pub fn go_root(&mut self) {
-        self.tree_path = vec![];
+        self.selection.clear();
+        self.set_tree_path(vec![]);
         self.fix_index();
     }
 
@@ -407,6 +865,7 @@ impl App {
     pub fn traverse_up(&mut self) -> bool {
         self.update_show_done_restriction();
         if let Some(index) = self.tree_path.pop() {
+            self.update_current_list();
             self.index = index;
             true
         } else {
@@ -428,66 +887,55 @@ impl App {
     }
 
     #[inline]
-    pub fn todo_mut(&mut self) -> Option<&mut Todo> {
+    pub fn todo_mut(&mut self) -> Option<BorrowedMut<'_, Todo>> {
         let index = self.index;
         let restriction = self.restriction.clone();
-        self.current_list_mut().index_mut(index, &restriction)
+        self.current_list_mut().filter_map(|list| list.index_mut(index, &restriction))
     }
 
     #[inline]
-    pub fn current_list_mut(&mut self) -> &mut TodoList {
+    pub fn current_list_mut(&mut self) -> BorrowedMut<'_, TodoList> {
+        self.record_undo(&self.current.clone());
+        // Indices of the selection don't follow the changes
+        self.selection.clear();
         self.changed = true;
-        let is_root = self.is_root();
-        if is_root {
-            return &mut self.todo_list;
-        }
-        let mut list = &mut self.todo_list;
-
-        for &index in &self.tree_path {
-            if list.todos[index].dependency.is_some() {
-                list = &mut list.todos[index].dependency.as_mut().unwrap().todo_list
+        match self.current.as_ref() {
+            Some(list) => BorrowedMut::Cell(edit_list(list)),
+            None => {
+                self.todo_list.count_change();
+                BorrowedMut::Plain(&mut self.todo_list)
             }
         }
-        list
     }
 
     #[inline]
-    pub fn current_list(&self) -> &TodoList {
-        let mut list = &self.todo_list;
-        if self.is_root() {
-            return list;
-        }
-        for &index in &self.tree_path {
-            if let Some(todo_list) = &list.todos[index]
-                .dependency
-                .as_ref()
-                .and_then(|dep| dep.todo_list())
-            {
-                list = todo_list
-            } else {
-                break;
-            }
-        }
-        list
+    pub fn current_list(&self) -> Borrowed<'_, TodoList> {
+        self.borrow_list(&self.current)
     }
 
     #[inline]
-    pub fn handle_removed_todo_dependency_files(&mut self, dependency_path: &Path) {
+    pub fn handle_removed_todo_dependency_files(&mut self, dependency_path: &Path, refs: &HashMap<String, usize>) {
         for todo in &mut self.removed_todos {
-            let _ = todo.delete_dependency_file(dependency_path);
+            let _ = todo.delete_dependency_file(dependency_path, refs);
         }
         self.removed_todos = vec![];
     }
 
     #[inline]
     pub fn write(&mut self) -> io::Result<()> {
+        if self.is_read_only() {
+            return Ok(());
+        }
         let note_dir = fileio::append_notes_to_path_parent(&self.args.todo_path);
 
         create_dir_all(&note_dir)?;
         let todo_path = self.args.todo_path.clone();
-        self.handle_removed_todo_dependency_files(&note_dir);
+        self.update_shared_dependencies();
+        let refs = self.todo_list.dependency_refs();
+        self.handle_removed_todo_dependency_files(&note_dir, &refs);
         self.todo_list.write(&todo_path)?;
-        self.todo_list.delete_removed_dependent_files(&note_dir)?;
+        self.todo_list.delete_removed_dependent_files(&note_dir, &refs)?;
+        self.todo_list.write_histories(&note_dir)?;
         if self.is_tree() {
             self.todo_list.write_dependencies(&note_dir)?;
         }
@@ -495,6 +943,106 @@ impl App {
         Ok(())
     }
 
+    /// Starts a new undo step, undo goes back to the state before the next change. Changes
+    /// that aren't in a step can't be undone on their own.
+    #[inline]
+    pub fn begin_undo_step(&mut self) {
+        self.undo_history.begin_step();
+    }
+
+    /// The lists of snapshot as they are now, with the current position
+    fn snapshot_of(&self, snapshot: &Snapshot) -> Snapshot {
+        Snapshot {
+            lists: snapshot.lists.iter().map(|saved| self.save_list(&saved.list)).collect(),
+            tree_path: self.tree_path.clone(),
+            index: self.index,
+            removed_todos: self.removed_todos.clone(),
+        }
+    }
+
+    #[inline]
+    fn save_list(&self, list: &ListCell) -> SavedList {
+        SavedList {
+            list: list.clone(),
+            todo_list: self.borrow_list(list).clone(),
+        }
+    }
+
+    /// Takes a snapshot before the first change of an undo step, and keeps list as it is
+    /// before the first change of it in the step
+    fn record_undo(&mut self, list: &ListCell) {
+        if self.undo_history.take_step() {
+            let snapshot = Snapshot {
+                lists: vec![],
+                tree_path: self.tree_path.clone(),
+                index: self.index,
+                removed_todos: self.removed_todos.clone(),
+            };
+            self.undo_history.push(snapshot);
+        }
+        if self.undo_history.recording().is_some_and(|snapshot| !snapshot.contains(list)) {
+            let saved = self.save_list(list);
+            if let Some(snapshot) = self.undo_history.recording() {
+                snapshot.lists.push(saved);
+            }
+        }
+    }
+
+    /// Records the todo lists of the todos at positions of the current list and the lists of
+    /// their trees, for changes that go into them
+    fn record_undo_of_dependencies(&mut self, positions: &[usize]) {
+        let mut lists = vec![];
+        let list = self.current_list();
+        for todo in positions.iter().filter_map(|&position| list.todos.get(position)) {
+            Self::collect_lists(todo, &mut lists);
+        }
+        drop(list);
+        for list in lists {
+            self.record_undo(&Some(list));
+        }
+    }
+
+    fn collect_lists(todo: &Todo, lists: &mut Vec<Rc<RefCell<TodoList>>>) {
+        if let Some(dependency) = todo.dependency.as_ref().filter(|dep| dep.is_list()) {
+            lists.push(dependency.shared_list().clone());
+            for todo in dependency.shared_list().borrow().iter() {
+                Self::collect_lists(todo, lists);
+            }
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        for saved in snapshot.lists {
+            let mut list = self.borrow_list_mut(&saved.list);
+            *list = saved.todo_list;
+            // Files may have been written since the snapshot
+            list.mark_unwritten();
+        }
+        self.set_tree_path(snapshot.tree_path);
+        self.index = snapshot.index;
+        self.removed_todos = snapshot.removed_todos;
+        self.selection.clear();
+        self.changed = true;
+        self.update_blockers();
+        self.fix_index();
+    }
+
+    /// Goes back to the state before the last undo step, returns whether there was one
+    pub fn undo(&mut self) -> bool {
+        let mut history = std::mem::take(&mut self.undo_history);
+        let snapshot = history.undo(|snapshot| self.snapshot_of(snapshot));
+        self.undo_history = history;
+        snapshot.map(|snapshot| self.restore(snapshot)).is_some()
+    }
+
+    /// Redoes the last undone step, returns whether there was one
+    pub fn redo(&mut self) -> bool {
+        let mut history = std::mem::take(&mut self.undo_history);
+        let snapshot = history.redo(|snapshot| self.snapshot_of(snapshot));
+        self.undo_history = history;
+        snapshot.map(|snapshot| self.restore(snapshot)).is_some()
+    }
+
     #[inline]
     pub fn is_root(&self) -> bool {
         self.tree_path.is_empty()
@@ -507,18 +1055,19 @@ impl App {
 
     #[inline]
     pub fn toggle_current_daily(&mut self) {
-        if let Some(todo) = self.todo_mut() {
-            todo.toggle_daily();
-            self.reorder_current();
+        self.edit_selected(Todo::toggle_daily)
+    }
+
+    #[inline]
+    pub fn toggle_current_resets_dependencies(&mut self) {
+        if let Some(mut schedule) = self.todo_mut().and_then(|todo| todo.filter_map(|todo| todo.schedule.as_mut())) {
+            schedule.toggle_resets_dependencies();
         }
     }
 
     #[inline]
     pub fn toggle_current_weekly(&mut self) {
-        if let Some(todo) = self.todo_mut() {
-            todo.toggle_weekly();
-            self.reorder_current();
-        }
+        self.edit_selected(Todo::toggle_weekly)
     }
 
     #[inline]
@@ -540,26 +1089,25 @@ impl App {
 
     #[inline(always)]
     pub fn unset_restriction(&mut self) {
+        self.selection.clear();
         self.restriction = Self::no_restriction();
     }
 
     #[inline(always)]
     pub fn set_restriction(&mut self, restriction: Restriction) {
+        self.selection.clear();
         self.restriction = restriction;
         self.fix_index();
     }
 
     #[inline]
     pub fn set_current_priority(&mut self, priority: u8) {
-        if let Some(todo) = self.todo_mut() {
-            todo.set_priority(priority);
-            self.reorder_current();
-        }
+        self.edit_selected(|todo| todo.set_priority(priority))
     }
 
     #[inline]
-    pub fn todo(&self) -> Option<&Todo> {
-        self.current_list().index(self.index, &self.restriction)
+    pub fn todo(&self) -> Option<Borrowed<'_, Todo>> {
+        self.current_list().filter_map(|list| list.index(self.index, &self.restriction))
     }
 
     #[inline]
@@ -573,7 +1121,19 @@ impl App {
     #[inline]
     pub fn remove_todo(&mut self) {
         let restriction = self.restriction.clone();
-        if !self.is_todos_empty() {
+        if !self.selection.is_empty() {
+            let indices = self.selected_indices();
+            let mut list = self.current_list_mut();
+            let mut removed: Vec<Todo> = indices
+                .into_iter()
+                .rev()
+                .map(|index| list.remove(index, &restriction))
+                .collect();
+            drop(list);
+            removed.reverse();
+            self.removed_todos.append(&mut removed);
+            self.fix_index();
+        } else if !self.is_todos_empty() {
             let index = self.index;
             let todo = self.current_list_mut().remove(index, &restriction);
             self.removed_todos.push(todo);
@@ -581,6 +1141,100 @@ impl App {
         }
     }
 
+    /// Removes the selected todos (or the current one) without deleting their dependency files
+    #[inline]
+    pub fn cut_todos(&mut self) -> Vec<Todo> {
+        let removed_len = self.removed_todos.len();
+        self.remove_todo();
+        self.removed_todos.split_off(removed_len)
+    }
+
+    /// Copies of the selected todos, or of the current one
+    #[inline]
+    pub fn selected_todos(&self) -> Vec<Todo> {
+        if self.selection.is_empty() {
+            return self.todo().map(|todo| todo.clone()).into_iter().collect();
+        }
+        let list = self.current_list();
+        self.selected_indices()
+            .into_iter()
+            .filter_map(|index| list.index(index, &self.restriction).cloned())
+            .collect()
+    }
+
+    /// Runs f on the selected todos (or the current one) and reorders them, the selection is
+    /// cleared after it
+    pub fn edit_selected(&mut self, mut f: impl FnMut(&mut Todo)) {
+        if self.selection.is_empty() {
+            if self.todo_mut().map(|mut todo| f(&mut todo)).is_some() {
+                self.reorder_current();
+            }
+            return;
+        }
+        let positions = self.selected_positions();
+        let mut list = self.current_list_mut();
+        for position in positions {
+            f(&mut list.todos[position]);
+        }
+        list.sort();
+        drop(list);
+        self.fix_index();
+    }
+
+    #[inline]
+    pub fn toggle_mark(&mut self) {
+        if !self.is_todos_empty() {
+            self.selection.toggle_mark(self.index);
+        }
+    }
+
+    #[inline]
+    pub fn toggle_visual(&mut self) {
+        self.selection.toggle_visual(self.index);
+    }
+
+    #[inline]
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    #[inline]
+    pub fn is_visual(&self) -> bool {
+        self.selection.is_visual()
+    }
+
+    #[inline]
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selection.contains(index, self.index)
+    }
+
+    /// Indices of the selected todos in the restricted list
+    #[inline]
+    pub fn selected_indices(&self) -> Vec<usize> {
+        let len = self.current_list().len(&self.restriction);
+        self.selection.indices(self.index, len)
+    }
+
+    /// Positions of the selected todos in the current list, regardless of the restriction
+    fn selected_positions(&self) -> Vec<usize> {
+        let positions = self.restricted_positions();
+        self.selected_indices()
+            .into_iter()
+            .map(|index| positions[index])
+            .collect()
+    }
+
+    /// Positions in the current list of the todos that the restriction shows
+    fn restricted_positions(&self) -> Vec<usize> {
+        self.current_list()
+            .todos
+            .iter()
+            .enumerate()
+            .filter(|(_, todo)| (self.restriction)(todo))
+            .map(|(position, _)| position)
+            .collect()
+    }
+
     #[inline(always)]
     pub fn display_current_list(&self) -> Vec<String> {
         self.current_list().display(&self.args.display_args, &self.restriction)
@@ -594,14 +1248,14 @@ impl App {
 
     #[inline]
     pub fn remove_current_dependent(&mut self) {
-        if let Some(todo) = self.todo_mut() {
+        if let Some(mut todo) = self.todo_mut() {
             todo.remove_dependency();
         }
     }
 
     #[inline]
     pub fn add_dependency(&mut self) {
-        if let Some(todo) = self.todo_mut() {
+        if let Some(mut todo) = self.todo_mut() {
             todo.add_todo_dependency();
         }
     }
@@ -611,10 +1265,15 @@ impl App {
         if self.is_tree() {
             let list_changed = self.current_list().changed;
             let changed = self.changed;
-            if let Some(todo) = self.todo_mut() {
-                if !todo.edit_note().unwrap_or_default() {
+            let step_started = self.undo_history.is_step_started();
+            let edited = self.todo_mut().map(|mut todo| todo.edit_note().unwrap_or_default());
+            if let Some(edited) = edited {
+                if !edited {
                     self.current_list_mut().changed = list_changed;
                     self.changed = changed;
+                    if step_started {
+                        self.undo_history.pop();
+                    }
                 }
             }
         }
@@ -633,13 +1292,14 @@ impl App {
             .index(index, &restriction)
             .map(|x| x.priority());
         if current_priority.is_some() && current_priority == next_priority {
-            let list = self.current_list_mut();
+            let mut list = self.current_list_mut();
             list.changed = true;
-            self.index = list.move_index(index, index + 1, 0);
+            let index = list.move_index(index, index + 1, 0);
+            drop(list);
+            self.index = index;
             return;
         }
-        if let Some(todo) = self.todo_mut() {
-            todo.decrease_priority();
+        if self.todo_mut().map(|mut todo| todo.decrease_priority()).is_some() {
             self.reorder_current();
         }
     }
@@ -660,13 +1320,14 @@ impl App {
             .index(index, &restriction)
             .map(|x| x.priority());
         if current_priority.is_some() && current_priority == prev_priority {
-            let list = self.current_list_mut();
+            let mut list = self.current_list_mut();
             list.changed = true;
-            self.index = list.move_index(index, index - 1, 1);
+            let index = list.move_index(index, index - 1, 1);
+            drop(list);
+            self.index = index;
             return;
         }
-        if let Some(todo) = self.todo_mut() {
-            todo.increase_priority();
+        if self.todo_mut().map(|mut todo| todo.increase_priority()).is_some() {
             self.reorder_current();
         }
     }
@@ -676,14 +1337,288 @@ impl App {
         if self.is_tree() {
             // The reason we are using a self.todo() here, is that if we don't want to
             // change anything, we won't borrow mutable and set the self.changed=true
-            if let Some(todo) = self.todo() {
-                if todo.dependency.is_none() {
-                    self.todo_mut().unwrap().add_todo_dependency();
-                }
+            if self.todo().is_some_and(|todo| todo.dependency.is_none()) {
+                self.todo_mut().unwrap().add_todo_dependency();
             }
             self.traverse_down()
         }
     }
+
+    /// Goes to the todo at position (see TodoList::tree_position), showing it even if the
+    /// restriction hides it
+    pub fn go_to(&mut self, position: &[usize]) -> bool {
+        let Some((&index, tree_path)) = position.split_last() else {
+            return false;
+        };
+        self.set_tree_path(tree_path.to_vec());
+        self.update_show_done_restriction();
+        if !self.current_list().todos.get(index).is_some_and(|todo| (self.restriction)(todo)) {
+            self.unset_restriction();
+        }
+        match self.restricted_positions().iter().position(|&position| position == index) {
+            Some(index) => {
+                self.index = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rows of the todos of the whole tree that the restriction doesn't hide, without the todos
+    /// of folded lists. They're kept until the tree changes.
+    #[inline]
+    pub fn outline(&self) -> Rc<Vec<OutlineRow>> {
+        self.outline.borrow_mut().rows(&self.folds, &self.todo_list, &self.restriction, &self.tree_path)
+    }
+
+    /// Position of the current todo in the whole tree, like the positions of outline rows
+    #[inline]
+    pub fn position(&self) -> Option<Vec<usize>> {
+        let index = *self.restricted_positions().get(self.index)?;
+        let mut position = self.tree_path.clone();
+        position.push(index);
+        Some(position)
+    }
+
+    /// Goes to the todo at position of the whole tree (like the positions of outline rows),
+    /// keeping the restriction
+    pub fn go_to_position(&mut self, position: &[usize]) {
+        let Some((&index, tree_path)) = position.split_last() else {
+            return;
+        };
+        if tree_path != self.tree_path {
+            self.set_tree_path(tree_path.to_vec());
+            self.selection.clear();
+        }
+        match self.restricted_positions().iter().position(|&position| position == index) {
+            Some(index) => self.index = index,
+            None => self.fix_index(),
+        }
+    }
+
+    /// Goes to the list at depth of the tree path (0 is the root list), to the todo that
+    /// the current list is in. Returns whether the current list is deeper than depth.
+    pub fn go_to_depth(&mut self, depth: usize) -> bool {
+        if depth >= self.tree_path.len() {
+            return false;
+        }
+        let position = self.tree_path[..=depth].to_vec();
+        self.go_to_position(&position);
+        true
+    }
+
+    /// Todos that the lists of the tree path are in, from the one in the root list to the parent
+    /// of the current list
+    pub fn parents(&self) -> Vec<Todo> {
+        let mut parents = vec![];
+        self.walk_path(&self.tree_path, |todo| parents.push(todo.clone()));
+        parents
+    }
+
+    /// Folds the todo list of the current todo, or the list the current todo is in (going to its
+    /// parent), like `zc` of vim
+    pub fn close_fold(&mut self) {
+        let name = self.todo().and_then(|todo| Self::foldable_list_name(&todo));
+        if let Some(name) = name.filter(|name| !self.folds.contains(name)) {
+            self.folds.fold(&name);
+            return;
+        }
+        let Some(depth) = self.tree_path.len().checked_sub(1) else {
+            return;
+        };
+        self.go_to_depth(depth);
+        if let Some(name) = self.todo().and_then(|todo| Self::foldable_list_name(&todo)) {
+            self.folds.fold(&name);
+        }
+    }
+
+    /// Unfolds the todo list of the current todo, like `zo` of vim
+    #[inline]
+    pub fn open_fold(&mut self) {
+        if let Some(name) = self.todo().and_then(|todo| Self::foldable_list_name(&todo)) {
+            self.folds.unfold(&name);
+        }
+    }
+
+    /// Folds the todo list of the current todo if it's unfolded and unfolds it otherwise, like
+    /// `za` of vim
+    pub fn toggle_fold(&mut self) {
+        if let Some(name) = self.todo().and_then(|todo| Self::foldable_list_name(&todo)) {
+            if !self.folds.unfold(&name) {
+                self.folds.fold(&name);
+            }
+        }
+    }
+
+    #[inline]
+    pub fn open_all_folds(&mut self) {
+        self.folds.clear()
+    }
+
+    /// Folds every todo list of the tree, like `zM` of vim
+    pub fn close_all_folds(&mut self) {
+        let names = self.todo_list.tree_filter_map(Self::foldable_list_name);
+        for name in names {
+            self.folds.fold(&name);
+        }
+    }
+
+    /// Name of the todo list of todo, if it has todos to fold
+    #[inline]
+    fn foldable_list_name(todo: &Todo) -> Option<String> {
+        todo.dependency
+            .as_ref()
+            .filter(|dep| dep.todo_list().is_some_and(|list| !list.todos.is_empty()))
+            .map(|dep| dep.name().to_string())
+    }
+
+    /// Positions in the current list of the selected todos, or of the current one
+    fn moving_positions(&self) -> Vec<usize> {
+        if !self.selection.is_empty() {
+            return self.selected_positions();
+        }
+        self.restricted_positions().get(self.index).copied().into_iter().collect()
+    }
+
+    /// The todo at position of the whole tree, like the positions of outline rows
+    pub fn todo_at(&self, position: &[usize]) -> Option<Todo> {
+        let (&index, tree_path) = position.split_last()?;
+        let (list, depth) = self.walk_path(tree_path, |_| {});
+        if depth < tree_path.len() {
+            return None;
+        }
+        let todo = self.borrow_list(&list).todos.get(index).cloned();
+        todo
+    }
+
+    /// Whether the todo at position has a todo list, or can have one
+    fn can_hold_todos(&self, position: &[usize]) -> bool {
+        let Some((&index, parent_path)) = position.split_last() else {
+            return true;
+        };
+        let (list, depth) = self.walk_path(parent_path, |_| {});
+        if depth < parent_path.len() {
+            return false;
+        }
+        let list = self.borrow_list(&list);
+        list.todos
+            .get(index)
+            .is_some_and(|todo| todo.dependency.as_ref().is_none_or(|dependency| dependency.is_list()))
+    }
+
+    /// Moves the selected todos (or the current one) to the list at tree_path, which is added
+    /// if its todo doesn't have one, and goes there. Their dependency files stay as they are,
+    /// every list of the tree shares the notes dir. Returns whether they moved.
+    pub fn move_to(&mut self, mut tree_path: Vec<usize>) -> bool {
+        let moving = self.moving_positions();
+        if !self.is_tree() || moving.is_empty() || tree_path == self.tree_path || !self.can_hold_todos(&tree_path) {
+            return false;
+        }
+        let depth = self.tree_path.len();
+        if tree_path.len() > depth && tree_path.starts_with(&self.tree_path) {
+            let index = tree_path[depth];
+            if moving.contains(&index) {
+                return false;
+            }
+            // Todos after the moved ones go up when they are taken out
+            tree_path[depth] -= moving.iter().filter(|&&position| position < index).count();
+        }
+        let todos = self.cut_todos();
+        if let Some((&index, parent_path)) = tree_path.split_last() {
+            let list = self.walk_path(parent_path, |_| {}).0;
+            self.record_undo(&list);
+            let mut list = self.borrow_list_mut(&list);
+            if list.todos[index].dependency.is_none() {
+                list.changed = true;
+                list.todos[index].add_todo_dependency();
+            }
+        }
+        self.set_tree_path(tree_path);
+        self.update_show_done_restriction();
+        let mut list = self.current_list_mut();
+        let mut index = 0;
+        for todo in todos {
+            list.push(todo);
+            index = list.reorder_last();
+        }
+        drop(list);
+        self.index = index;
+        self.fix_index();
+        self.update_shared_dependencies();
+        true
+    }
+
+    #[inline]
+    pub fn move_to_parent(&mut self) -> bool {
+        match self.tree_path.split_last() {
+            Some((_, tree_path)) => self.move_to(tree_path.to_vec()),
+            None => false,
+        }
+    }
+
+    /// Moves into the todo list of the todo at index of the current list
+    pub fn move_into(&mut self, index: usize) -> bool {
+        let Some(&position) = self.restricted_positions().get(index) else {
+            return false;
+        };
+        let mut tree_path = self.tree_path.clone();
+        tree_path.push(position);
+        self.move_to(tree_path)
+    }
+
+    /// Moves into the todo list of the first todo of the current list that matches query
+    pub fn move_into_match(&mut self, query: &str) -> bool {
+        let moving = self.moving_positions();
+        let positions = self.restricted_positions();
+        let index = self
+            .current_list()
+            .todos(&self.restriction)
+            .zip(&positions)
+            .position(|(todo, position)| !moving.contains(position) && todo.matches(query));
+        index.is_some_and(|index| self.move_into(index))
+    }
+
+    /// Moves into the todo list of the first todo of the whole tree that matches query
+    pub fn move_to_match(&mut self, query: &str) -> bool {
+        let moving = self.moving_positions();
+        let tree_path = self.tree_path.clone();
+        let depth = tree_path.len();
+        let position = self.todo_list.tree_position(&|position, todo| {
+            let is_moved = position.len() > depth && position.starts_with(&tree_path) && moving.contains(&position[depth]);
+            !is_moved && todo.matches(query)
+        });
+        position.is_some_and(|position| self.move_to(position))
+    }
+
+    /// Moves the selected todos (or the current one) with their dependencies to another todo
+    /// file and writes both files. Their dependency files go to the notes dir of that file.
+    /// Returns how many todos moved.
+    pub fn move_to_file(&mut self, path: &Path) -> io::Result<usize> {
+        let is_same_path = |a: &Path, b: &Path| a.canonicalize().unwrap_or(a.to_path_buf()) == b.canonicalize().unwrap_or(b.to_path_buf());
+        if self.is_read_only() || is_same_path(path, &self.args.todo_path) {
+            return Ok(0);
+        }
+        let mut todos = self.selected_todos();
+        if todos.is_empty() {
+            return Ok(0);
+        }
+        let count = todos.len();
+        let notes_dir = fileio::append_notes_to_path_parent(path);
+        let mut list = Self::read_a_todo_list(path, &notes_dir, &self.args);
+        list.todos.append(&mut todos);
+        list.sort();
+        // The todos are only cut once they're in the other file
+        Self::write_list_to_path(&list, path)?;
+        let todos = self.cut_todos();
+        // Dependencies aren't read without the tree, so their files are left alone
+        if self.is_tree() && !is_same_path(&notes_dir, &self.notes_dir) {
+            self.removed_todos.extend(todos);
+        }
+        self.write()?;
+        // The other file is already written, undoing would bring back a copy of its todos
+        self.undo_history.clear();
+        Ok(count)
+    }
 }
 
 pub mod test_helpers {
@@ -738,7 +1673,6 @@ pub mod test_helpers {
 mod tests {
     use std::fs::{self, remove_dir_all};
     use super::test_helpers::*;
-    use crate::date;
     use super::*;
 
     #[test]
@@ -895,6 +1829,533 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_progress() -> io::Result<()> {
+        let mut app = get_test_app(AppArgs {
+            todo_path: PathBuf::from("test-progress/todo"),
+            ..Default::default()
+        })?;
+        let display = |app: &App| app.display_current_list().into_iter().find(|line| line.contains("Hello there")).unwrap();
+        assert!(display(&app).ends_with("Hello there [0/3]"));
+        app.traverse_down();
+        app.traverse_down();
+        app.todo_mut().unwrap().set_done(true);
+        app.go_root();
+        assert!(display(&app).ends_with("Hello there [1/3]"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_changes() -> io::Result<()> {
+        let dir = dir("test-list-changes")?;
+        let mut app = write_test_todos(&dir)?;
+        let other = App::new(AppArgs {
+            todo_path: dir.join("todo"),
+            ..Default::default()
+        });
+        let other_stamp = other.todo_list.changes().stamp().unwrap();
+        app.todo_mut();
+        let stamp = app.todo_list.changes().stamp().unwrap();
+        app.write()?;
+        assert!(stamp.is_current(app.todo_list.changes()));
+        assert!(other_stamp.is_current(other.todo_list.changes()));
+
+        let rows = app.outline().len();
+        app.todo_list.push(Todo::new("Direct".to_string(), 0));
+        assert_eq!(app.outline().len(), rows + 1);
+
+        let display = |app: &App| app.display_current_list().into_iter().find(|line| line.contains("Hello there")).unwrap();
+        let position = app.todo_list.tree_position(&|_, todo| todo.message == "Is there anyone home").unwrap();
+        assert!(app.go_to(&position));
+        app.add_dependency();
+        app.go_root();
+        assert!(display(&app).ends_with("Hello there [0/3]"));
+        assert!(app.go_to(&position));
+        app.traverse_down();
+        app.append(String::from("Is it you?"));
+        app.go_root();
+        remove_dir_all(dir)?;
+        assert!(display(&app).ends_with("Hello there [0/4]"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_completed() -> io::Result<()> {
+        let mut app = get_test_app(AppArgs {
+            todo_path: PathBuf::from("test-record-completed/todo"),
+            ..Default::default()
+        })?;
+        app.toggle_show_done();
+        app.toggle_current_done();
+        assert_eq!(app.todo().unwrap().completed(), None);
+        app.toggle_current_done();
+        app.args.record_created = true;
+        app.toggle_current_done();
+        assert_eq!(app.todo().unwrap().completed(), Some(date::current()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_done_propagation() -> io::Result<()> {
+        let mut app = get_test_app(AppArgs {
+            todo_path: PathBuf::from("test-done-propagation/todo"),
+            ..Default::default()
+        })?;
+        app.toggle_show_done();
+        app.traverse_down();
+        app.toggle_current_done();
+        assert_eq!(app.tree_path, vec![]);
+        assert!(app.todo().unwrap().done());
+        app.traverse_down();
+        app.toggle_current_done();
+        assert_eq!(app.tree_path, vec![2]);
+        app.traverse_up();
+        assert!(!app.todo().unwrap().done());
+
+        app.args.done_propagation = DonePropagation::Children;
+        app.toggle_current_done();
+        let display = app.display_current_list().into_iter().find(|line| line.contains("Hello there")).unwrap();
+        assert!(display.ends_with("[3/3]"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_dependency() -> io::Result<()> {
+        let dir = dir("test-shared-dependency")?;
+        let mut app = write_test_todos(&dir)?;
+        let mut todo = app.todo().unwrap().clone();
+        todo.message = String::from("Linked");
+        let mut list = app.current_list_mut();
+        list.push(todo);
+        let linked_index = list.reorder_last();
+        drop(list);
+        app.update_shared_dependencies();
+        assert!(app.todo().unwrap().to_string().starts_with("0="));
+        let with_progress = |app: &App, progress: &str| app.display_current_list().iter().filter(|line| line.ends_with(progress)).count();
+        assert_eq!(with_progress(&app, "[0/3]"), 2);
+
+        app.traverse_down();
+        app.append(String::from("Seen from both"));
+        app.traverse_up();
+        app.index = linked_index;
+        app.traverse_down();
+        assert!(app.current_list().iter().any(|todo| todo.message == "Seen from both"));
+        app.traverse_up();
+        assert_eq!(with_progress(&app, "[0/4]"), 2);
+
+        app.remove_todo();
+        app.write()?;
+        let notes_count = fs::read_dir(dir.join("notes"))?.count();
+        remove_dir_all(dir)?;
+        assert_eq!(notes_count, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_template() -> io::Result<()> {
+        let dir = dir("test-template")?;
+        let mut app = write_test_todos(&dir)?;
+        app.todo_mut().unwrap().message = String::from("Release {{version}}");
+        app.save_current_as_template("release")?;
+        let vars = template::parse_vars(["version=1.8"]);
+        assert!(app.append_template("release", None, &vars));
+        assert!(!app.append_template("unknown", None, &vars));
+        app.write()?;
+
+        let template_names = template::names(&app.args.todo_path);
+        let original = app.todo_list.iter().find(|todo| todo.message == "Release {{version}}").cloned();
+        let copy = app.todo_list.iter().find(|todo| todo.message == "Release 1.8").cloned();
+        let notes_count = fs::read_dir(dir.join("notes"))?.count();
+        remove_dir_all(dir)?;
+        assert_eq!(template_names, vec!["release"]);
+        let (original, copy) = (original.unwrap(), copy.unwrap());
+        assert_ne!(original.dependency.as_ref().unwrap().name(), copy.dependency.as_ref().unwrap().name());
+        assert_eq!(copy.dependency.unwrap().todo_list().unwrap().tree_filter_map(|_| Some(())).len(), 3);
+        // three todo lists of the copy, the note has the same content so it's the same file
+        assert_eq!(notes_count, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo() -> io::Result<()> {
+        let dir = dir("test-undo")?;
+        let mut app = write_test_todos(&dir)?;
+        let messages = |app: &App| app.current_list().iter().map(|todo| todo.message.clone()).collect::<Vec<_>>();
+        let original = messages(&app);
+        assert!(!app.undo());
+
+        app.begin_undo_step();
+        app.append(String::from("New"));
+        let appended = messages(&app);
+        app.begin_undo_step();
+        app.toggle_current_done();
+        app.remove_todo();
+        app.write()?;
+
+        assert!(app.undo());
+        assert_eq!(messages(&app), appended);
+        assert!(app.removed_todos.is_empty());
+        assert!(app.undo());
+        assert_eq!(messages(&app), original);
+        assert!(!app.undo());
+        assert!(app.redo());
+        assert_eq!(messages(&app), appended);
+        app.write()?;
+        let written = TodoList::read(&dir.join("todo")).todos.len();
+        remove_dir_all(dir)?;
+        assert_eq!(written, appended.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_nested() -> io::Result<()> {
+        let dir = dir("test-undo-nested")?;
+        let mut app = write_test_todos(&dir)?;
+        app.args.done_propagation = DonePropagation::Children;
+        app.toggle_show_done();
+        app.traverse_down();
+        app.begin_undo_step();
+        app.toggle_current_done();
+        app.begin_undo_step();
+        app.traverse_down();
+        app.append(String::from("New"));
+        assert_eq!(app.current_list().todos.len(), 2);
+
+        assert!(app.undo());
+        assert_eq!(app.tree_path, vec![2, 0]);
+        assert_eq!(app.current_list().todos.len(), 1);
+        assert!(app.current_list().todos[0].done());
+        assert!(app.undo());
+        assert_eq!(app.tree_path, vec![2]);
+        app.traverse_down();
+        assert!(!app.current_list().todos[0].done());
+        app.read();
+        assert!(!app.undo());
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_selection() -> io::Result<()> {
+        let dir = dir("test-selection")?;
+        let mut app = write_test_todos(&dir)?;
+        app.append(String::from("Farewell"));
+        let len = app.current_list().len(app.get_restriction());
+        app.index = len - 1;
+        app.toggle_mark();
+        app.index = 0;
+        app.toggle_visual();
+        app.go_down();
+        assert_eq!(app.selected_indices(), [0, 1, len - 1]);
+        assert!(!app.is_selected(2));
+        let selected: Vec<String> = app.selected_todos().into_iter().map(|todo| todo.message).collect();
+
+        app.set_current_priority(9);
+        assert!(app.selected_indices().is_empty());
+        let list = app.current_list();
+        let prioritised: Vec<&Todo> = list.iter().filter(|todo| todo.priority() == 9).collect();
+        assert_eq!(prioritised.len(), 3);
+        assert!(prioritised.iter().all(|todo| selected.contains(&todo.message)));
+        drop(list);
+
+        app.index = 0;
+        app.toggle_visual();
+        app.go_down();
+        app.remove_todo();
+        assert_eq!(app.current_list().len(app.get_restriction()), len - 2);
+        assert_eq!(app.removed_todos.len(), 2);
+        assert_eq!(app.cut_todos().len(), 1);
+        assert_eq!(app.removed_todos.len(), 2);
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_tree_history() -> io::Result<()> {
+        let dir = dir("test-no-tree-history")?;
+        let mut app = write_test_todos(&dir)?;
+        app.toggle_current_daily();
+        app.toggle_current_done();
+        app.write()?;
+        let app = App::new(AppArgs {
+            todo_path: dir.join("todo"),
+            no_tree: true,
+            ..Default::default()
+        });
+        let stats = app.todo_list.iter().find_map(|todo| todo.schedule.as_ref()?.stats());
+        assert_eq!(stats.map(|stats| stats.completions), Some(1));
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_outline() -> io::Result<()> {
+        let dir = dir("test-outline")?;
+        let mut app = write_test_todos(&dir)?;
+        let messages = |app: &App| {
+            app.outline().iter().map(|row| app.todo_at(&row.position).unwrap().message.clone()).collect::<Vec<_>>()
+        };
+        let all = messages(&app);
+        assert_eq!(all.len(), app.todo_list.len(app.get_restriction()) + 3);
+        let rows = app.outline();
+        let row = rows.iter().find(|row| app.todo_at(&row.position).unwrap().message == "Is there anyone home").unwrap();
+        assert_eq!(row.depth(), 3);
+        assert!(row.prefix().ends_with("└── "));
+        let row = rows.iter().find(|row| app.todo_at(&row.position).unwrap().message == "Just nod if you can here me").unwrap();
+        app.go_to_position(&row.position);
+        assert_eq!(app.tree_path.len(), 2);
+        assert_eq!(app.position().as_ref(), Some(&row.position));
+
+        app.close_fold();
+        assert_eq!(messages(&app).len(), all.len() - 1);
+        app.close_fold();
+        assert_eq!(app.todo().unwrap().message, "Is there anybody outthere?");
+        assert_eq!(messages(&app).len(), all.len() - 2);
+        app.toggle_fold();
+        assert_eq!(messages(&app).len(), all.len() - 1);
+        app.open_all_folds();
+        assert_eq!(messages(&app), all);
+        app.go_root();
+        app.close_all_folds();
+        assert_eq!(messages(&app).len(), app.todo_list.len(app.get_restriction()));
+
+        // The rows are kept until a list changes
+        assert!(Rc::ptr_eq(&app.outline(), &app.outline()));
+        app.append(String::from("Hello again"));
+        assert_eq!(messages(&app).len(), app.todo_list.len(app.get_restriction()));
+        app.open_all_folds();
+        let len = messages(&app).len();
+        app.go_to_position(&row.position);
+        app.append(String::from("Nod again"));
+        assert_eq!(messages(&app).len(), len + 1);
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_go_to_depth() -> io::Result<()> {
+        let dir = dir("test-go-to-depth")?;
+        let mut app = write_test_todos(&dir)?;
+        let position = app.todo_list.tree_position(&|_, todo| todo.message == "Is there anyone home");
+        assert!(app.go_to(&position.unwrap()));
+        let parents: Vec<_> = app.parents().iter().map(|todo| todo.message.clone()).collect();
+        assert_eq!(parents, ["Hello there", "Is there anybody outthere?", "Just nod if you can here me"]);
+        assert!(!app.go_to_depth(3));
+        assert!(app.go_to_depth(1));
+        assert_eq!(app.parents().len(), 1);
+        assert_eq!(app.todo().unwrap().message, "Is there anybody outthere?");
+        assert!(app.go_to_depth(0));
+        assert!(app.is_root());
+        assert_eq!(app.todo().unwrap().message, "Hello there");
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_move() -> io::Result<()> {
+        let dir = dir("test-move")?;
+        let mut app = write_test_todos(&dir)?;
+        let messages = |list: &TodoList| list.iter().map(|todo| todo.message.clone()).collect::<Vec<_>>();
+        let position = app.todo_list.tree_position(&|_, todo| todo.message == "Just nod if you can here me");
+        assert!(app.go_to(&position.unwrap()));
+        assert!(app.move_to_parent());
+        assert_eq!(app.todo().unwrap().message, "Just nod if you can here me");
+        assert_eq!(app.tree_path.len(), 1);
+        assert_eq!(messages(&app.current_list()), ["Is there anybody outthere?", "Just nod if you can here me"]);
+
+        app.go_root();
+        app.index = 2;
+        assert!(!app.move_to_match("Just nod"));
+        app.index = 0;
+        assert!(app.move_into_match("Goodbye"));
+        assert_eq!(app.tree_path, [0]);
+        assert_eq!(app.todo().unwrap().message, "Hello");
+        assert!(app.move_to_parent());
+        assert!(app.is_root());
+        assert_eq!(app.todo().unwrap().message, "Hello");
+        app.write()?;
+
+        let other = dir.join("other");
+        fs::create_dir_all(&other)?;
+        app.go_root();
+        let position = app.todo_list.tree_position(&|_, todo| todo.message == "Hello there");
+        assert!(app.go_to(&position.unwrap()));
+        assert_eq!(app.move_to_file(&other.join("todo"))?, 1);
+        let mut moved = App::new(AppArgs {
+            todo_path: other.join("todo"),
+            ..Default::default()
+        });
+        assert_eq!(messages(&moved.todo_list), ["Hello there"]);
+        moved.traverse_down();
+        assert_eq!(messages(&moved.current_list()), ["Is there anybody outthere?", "Just nod if you can here me"]);
+        for entry in fs::read_dir(other.join("notes"))? {
+            assert!(!dir.join("notes").join(entry?.file_name()).exists());
+        }
+
+        // A file that can't be written keeps the todos
+        fs::write(dir.join("file"), "")?;
+        app.go_root();
+        let len = app.todo_list.todos.len();
+        assert!(app.move_to_file(&dir.join("file").join("todo")).is_err());
+        assert_eq!(app.todo_list.todos.len(), len);
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_register() -> io::Result<()> {
+        let dir = dir("test-register")?;
+        let registers = dir.join("registers");
+        let mut app = write_test_todos(&dir)?;
+        let position = app.todo_list.tree_position(&|_, todo| todo.message == "Hello there");
+        assert!(app.go_to(&position.unwrap()));
+        app.write_register(&registers, "a", &app.selected_todos())?;
+        assert_eq!(register::names(&registers), ["a"]);
+        assert!(!app.paste_register(&registers, "b"));
+        for name in ["", ".", "..", "/", "a/b"] {
+            let error = app.write_register(&registers, name, &app.selected_todos()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            assert!(!app.paste_register(&registers, name));
+        }
+        assert!(registers.join("a").join("todo").is_file());
+        assert!(app.paste_register(&registers, "a"));
+        assert!(app.paste_register_as_link(&registers, "a"));
+        app.write()?;
+
+        let app = App::new(AppArgs {
+            todo_path: dir.join("todo"),
+            ..Default::default()
+        });
+        let copies: Vec<&Todo> = app.todo_list.iter().filter(|todo| todo.message == "Hello there").collect();
+        assert_eq!(copies.len(), 3);
+        let (linked, copies): (Vec<&Todo>, Vec<&Todo>) = copies.into_iter().partition(|todo| todo.dependency.as_ref().unwrap().is_shared());
+        assert_eq!(linked.len(), 2);
+        assert_eq!(linked[0].dependency.as_ref().unwrap().name(), linked[1].dependency.as_ref().unwrap().name());
+        let copies = [linked[0], copies[0]];
+        let names = |todo: &Todo| todo.dependency.as_ref().unwrap().todo_list().unwrap().file_names();
+        assert!(names(copies[0]).len() > 1);
+        assert_eq!(names(copies[0]).len(), names(copies[1]).len());
+        assert_ne!(copies[0].dependency.as_ref().unwrap().name(), copies[1].dependency.as_ref().unwrap().name());
+        assert!(names(copies[0]).iter().filter(|name| name.ends_with(".todo")).all(|name| !names(copies[1]).contains(name)));
+        assert_eq!(app.todo_list.tree_filter_map(|_| Some(())).len(), 2 + 2 * 4 + 1);
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_paste_link_into_itself() -> io::Result<()> {
+        let dir = dir("test-paste-link-into-itself")?;
+        let registers = dir.join("registers");
+        let mut app = write_test_todos(&dir)?;
+        let position = app.todo_list.tree_position(&|_, todo| todo.message == "Hello there");
+        assert!(app.go_to(&position.unwrap()));
+        app.write_register(&registers, "a", &app.selected_todos())?;
+        app.traverse_down();
+        assert!(app.paste_register_as_link(&registers, "a"));
+        app.traverse_down();
+        assert!(app.paste_register_as_link(&registers, "a"));
+        app.write()?;
+
+        let app = App::new(AppArgs {
+            todo_path: dir.join("todo"),
+            ..Default::default()
+        });
+        remove_dir_all(dir)?;
+        let copies: Vec<&Todo> = app.todo_list.iter().filter(|todo| todo.message == "Hello there").collect();
+        assert_eq!(copies.len(), 1);
+        assert!(!copies[0].dependency.as_ref().unwrap().is_shared());
+        assert_eq!(app.todo_list.tree_filter_map(|_| Some(())).len(), 2 + 3 * 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive() -> io::Result<()> {
+        let dir = dir("test-archive")?;
+        let mut app = write_test_todos(&dir)?;
+        let index = app.current_list().todos.iter().position(|todo| todo.message == "Hello there").unwrap();
+        app.current_list_mut().todos[index].set_done(true);
+        app.current_list_mut().todos[index].ensure_completed();
+        app.current_list_mut().todos[0].set_done(true);
+        let last_month = date::current() - chrono::Duration::days(30);
+        app.current_list_mut().todos[0].set_attributes(format!("completed:{}", date::format(Some(last_month))).parse().unwrap());
+        assert_eq!(app.archive_done(Some(date::current()))?, 1);
+        assert_eq!(app.archive_done(None)?, 1);
+
+        let archive = App::new(AppArgs {
+            archive: true,
+            todo_path: dir.join("todo"),
+            ..Default::default()
+        });
+        let archived: Vec<String> = archive.todo_list.iter().map(|todo| todo.message.clone()).collect();
+        let tree_count = archive.todo_list.tree_filter_map(|_| Some(())).len();
+        let todos_count = app.todo_list.todos.len();
+        remove_dir_all(dir)?;
+        assert!(archive.is_read_only());
+        assert_eq!(todos_count, 1);
+        assert_eq!(archived.len(), 2);
+        assert_eq!(tree_count, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_own_file() -> io::Result<()> {
+        let dir = dir("test-archive-own-file")?;
+        let mut app = get_test_app(AppArgs {
+            todo_path: dir.join("archive"),
+            ..Default::default()
+        })?;
+        app.current_list_mut().todos[0].set_done(true);
+        app.write()?;
+        let archived = app.archive_done(None);
+        let read = App::new(AppArgs {
+            todo_path: dir.join("archive"),
+            ..Default::default()
+        });
+        remove_dir_all(dir)?;
+        assert_eq!(archived.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(read.todo_list.todos.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_nested() -> io::Result<()> {
+        let dir = dir("test-archive-nested")?;
+        let mut app = write_test_todos(&dir)?;
+        app.traverse_down();
+        app.traverse_down();
+        app.current_list_mut().todos[0].set_done(true);
+        assert_eq!(app.archive_done(None)?, 1);
+
+        let archive = App::new(AppArgs {
+            archive: true,
+            todo_path: dir.join("todo"),
+            ..Default::default()
+        });
+        let mut archived = vec![];
+        archive.todo_list.tree_for_each(|todo| archived.push(todo.message.clone()));
+        let left = app.current_list().todos.len();
+        remove_dir_all(dir)?;
+        assert_eq!(left, 0);
+        assert_eq!(archived, ["Hello there", "Is there anybody outthere?", "Just nod if you can here me", "Is there anyone home"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hide_blocked() -> io::Result<()> {
+        let dir = dir("test-hide-blocked")?;
+        let mut app = write_test_todos(&dir)?;
+        app.toggle_show_done();
+        app.toggle_hide_blocked();
+        app.index = 1;
+        assert!(app.block_current_by("Hello"));
+        assert_eq!(app.current_list().len(&app.restriction), 2);
+        app.todo_list.todos[0].set_done(true);
+        app.update_blockers();
+        assert_eq!(app.current_list().len(&app.restriction), 3);
+        remove_dir_all(dir)?;
+        Ok(())
+    }
+
     #[test]
     fn test_sort_method() -> io::Result<()> {
         let todo_path = dir("test-sort-method")?.join("todo");
@@ -904,7 +2365,7 @@ mod tests {
             ..Default::default()
         })?;
         app.append("a todo".to_string());
-        if let Some(todo) = app.todo_mut() {
+        if let Some(mut todo) = app.todo_mut() {
             todo.schedule = Some(Schedule::new_reminder(date::current()));
             todo.set_done(true);
         }
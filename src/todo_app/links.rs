@@ -0,0 +1,114 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use super::{Todo, TodoList};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+// }}}
+
+#[derive(Debug, PartialEq)]
+pub enum LinkProblem {
+    DuplicateId(String),
+    UnknownBlocker { message: String, id: String },
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for LinkProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateId(id) => write!(f, "id {id} is used by more than one todo"),
+            Self::UnknownBlocker { message, id } => {
+                write!(f, "\"{message}\" is blocked by {id}, which doesn't exist")
+            }
+            Self::Cycle(ids) => write!(f, "blocking cycle: {}", ids.join(" -> ")),
+        }
+    }
+}
+
+/// Checks blocked-by links of the whole tree for duplicate ids, unknown blockers and cycles
+pub fn check(todo_list: &TodoList) -> Vec<LinkProblem> {
+    let mut problems = vec![];
+    let tree_todos = todo_list.tree_filter_map(|todo| Some(todo.clone()));
+    let mut todos: HashMap<&str, &Todo> = HashMap::new();
+    for todo in tree_todos.iter() {
+        if let Some(id) = todo.id() {
+            if todos.insert(id, todo).is_some() {
+                problems.push(LinkProblem::DuplicateId(id.to_string()));
+            }
+        }
+    }
+    for todo in tree_todos.iter() {
+        for id in todo.blocked_by().filter(|id| !todos.contains_key(id)) {
+            problems.push(LinkProblem::UnknownBlocker {
+                message: todo.message.clone(),
+                id: id.to_string(),
+            });
+        }
+    }
+
+    let mut finished: HashSet<&str> = HashSet::new();
+    let mut ids: Vec<&str> = todos.keys().copied().collect();
+    ids.sort();
+    for id in ids {
+        let mut path = vec![];
+        find_cycles(id, &todos, &mut path, &mut finished, &mut problems);
+    }
+    problems
+}
+
+fn find_cycles<'a>(
+    id: &'a str,
+    todos: &HashMap<&'a str, &'a Todo>,
+    path: &mut Vec<&'a str>,
+    finished: &mut HashSet<&'a str>,
+    problems: &mut Vec<LinkProblem>,
+) {
+    if finished.contains(id) {
+        return;
+    }
+    if let Some(start) = path.iter().position(|&in_path| in_path == id) {
+        let mut cycle: Vec<String> = path[start..].iter().map(|id| id.to_string()).collect();
+        cycle.push(id.to_string());
+        problems.push(LinkProblem::Cycle(cycle));
+        return;
+    }
+    path.push(id);
+    if let Some(todo) = todos.get(id) {
+        for blocker in todo.blocked_by() {
+            if let Some((&blocker, _)) = todos.get_key_value(blocker) {
+                find_cycles(blocker, todos, path, finished, problems);
+            }
+        }
+    }
+    path.pop();
+    finished.insert(id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_check() {
+        let mut todo_list = TodoList::default();
+        for line in [
+            "[1] a {blocked-by:b id:a}",
+            "[1] b {blocked-by:c id:b}",
+            "[1] c {blocked-by:a id:c}",
+            "[1] d {blocked-by:x id:d}",
+        ] {
+            todo_list.push(Todo::from_str(line).unwrap());
+        }
+        let problems = check(&todo_list);
+        assert_eq!(
+            problems,
+            vec![
+                LinkProblem::UnknownBlocker {
+                    message: String::from("d"),
+                    id: String::from("x")
+                },
+                LinkProblem::Cycle(vec!["a", "b", "c", "a"].into_iter().map(String::from).collect()),
+            ]
+        );
+    }
+}
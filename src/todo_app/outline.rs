@@ -0,0 +1,138 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use super::todo_list::ChangeStamp;
+use super::{Restriction, Todo, TodoList};
+use std::collections::HashSet;
+use std::rc::Rc;
+// }}}
+
+/// A todo of the whole tree, shown as a line of an outline
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineRow {
+    /// True indices of the todo and its parents, from the root list
+    pub position: Vec<usize>,
+    /// Whether each parent (from the root list on) has todos after it
+    pub continues: Vec<bool>,
+    pub is_last: bool,
+    /// Whether the todo has a folded todo list
+    pub folded: bool,
+}
+
+impl OutlineRow {
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.continues.len()
+    }
+
+    /// Tree lines before the todo, the same as the ones of `c3 -l`
+    pub fn prefix(&self) -> String {
+        if self.continues.is_empty() {
+            return String::new();
+        }
+        let mut prefix: String = self.continues[1..]
+            .iter()
+            .map(|&continues| if continues { "│   " } else { "    " })
+            .collect();
+        prefix.push_str(if self.is_last { "└── " } else { "├── " });
+        prefix
+    }
+}
+
+/// Todo lists folded in the outline, by the names of their files
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(super) struct Folds {
+    names: HashSet<String>,
+}
+
+impl Folds {
+    #[inline]
+    pub(super) fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    #[inline]
+    pub(super) fn fold(&mut self, name: &str) {
+        self.names.insert(name.to_string());
+    }
+
+    #[inline]
+    pub(super) fn unfold(&mut self, name: &str) -> bool {
+        self.names.remove(name)
+    }
+
+    #[inline]
+    pub(super) fn clear(&mut self) {
+        self.names.clear();
+    }
+
+    /// Rows of the todos of the list and the unfolded todo lists in it. Lists on `open_path` (the
+    /// path of the current list) are never folded, so the current todo always has a row.
+    pub(super) fn rows(&self, todo_list: &TodoList, restriction: &Restriction, open_path: &[usize]) -> Vec<OutlineRow> {
+        let mut rows = vec![];
+        self.push_rows(&mut rows, todo_list, restriction, open_path, &mut vec![], &mut vec![]);
+        rows
+    }
+
+    fn push_rows(
+        &self,
+        rows: &mut Vec<OutlineRow>,
+        todo_list: &TodoList,
+        restriction: &Restriction,
+        open_path: &[usize],
+        position: &mut Vec<usize>,
+        continues: &mut Vec<bool>,
+    ) {
+        let todos: Vec<(usize, &Todo)> = todo_list
+            .todos
+            .iter()
+            .enumerate()
+            .filter(|(_, todo)| restriction(todo))
+            .collect();
+        for (i, &(index, todo)) in todos.iter().enumerate() {
+            let is_last = i + 1 == todos.len();
+            position.push(index);
+            todo_list.adopt(todo);
+            let list = todo.dependency.as_ref().and_then(|dep| Some((dep.name(), dep.todo_list()?)));
+            let folded = list.as_ref().is_some_and(|(name, list)| {
+                !list.todos.is_empty() && self.contains(name) && !open_path.starts_with(position)
+            });
+            rows.push(OutlineRow {
+                position: position.clone(),
+                continues: continues.clone(),
+                is_last,
+                folded,
+            });
+            if let Some((_, list)) = list.filter(|_| !folded) {
+                continues.push(!is_last);
+                self.push_rows(rows, &list, restriction, open_path, position, continues);
+                continues.pop();
+            }
+            position.pop();
+        }
+    }
+}
+
+/// Rows of the last outline with what they were made from, they're made again once a list of
+/// the tree, the open path, the restriction or the folds change
+#[derive(Default)]
+pub(super) struct OutlineCache {
+    made_from: Option<(ChangeStamp, Vec<usize>, Restriction, Folds)>,
+    rows: Rc<Vec<OutlineRow>>,
+}
+
+impl OutlineCache {
+    /// The rows of Folds::rows, made again only when they could be different
+    pub(super) fn rows(&mut self, folds: &Folds, todo_list: &TodoList, restriction: &Restriction, open_path: &[usize]) -> Rc<Vec<OutlineRow>> {
+        let is_valid = self.made_from.as_ref().is_some_and(|(made_at, path, last_restriction, last_folds)| {
+            made_at.is_current(todo_list.changes()) && path == open_path && Rc::ptr_eq(last_restriction, restriction) && last_folds == folds
+        });
+        if !is_valid {
+            self.rows = Rc::new(folds.rows(todo_list, restriction, open_path));
+            self.made_from = todo_list
+                .changes()
+                .stamp()
+                .map(|stamp| (stamp, open_path.to_vec(), restriction.clone(), folds.clone()));
+        }
+        self.rows.clone()
+    }
+}
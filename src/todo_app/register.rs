@@ -0,0 +1,45 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use super::template;
+use crate::fileio;
+use std::fs;
+use std::path::{Path, PathBuf};
+// }}}
+
+/// The register that is used when none is given
+pub const DEFAULT: &str = "default";
+
+/// Registers are kept as todo files (with their own notes directory) in `<dir>/<name>/todo`,
+/// dir is `$XDG_DATA_HOME/c3/registers` (see [`dir`]) so they are shared by every todo file and
+/// every c3 that is running.
+#[inline]
+pub fn path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(name).join("todo")
+}
+
+/// Names are directories of the registers directory like the names of templates, they can't be
+/// paths (see [`template::is_valid_name`])
+#[inline]
+pub fn is_valid_name(name: &str) -> bool {
+    template::is_valid_name(name)
+}
+
+/// The directory of the registers
+#[inline]
+pub fn dir() -> PathBuf {
+    fileio::data_dir().join("registers")
+}
+
+pub fn names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().join("todo").is_file())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
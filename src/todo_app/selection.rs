@@ -0,0 +1,63 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use std::collections::BTreeSet;
+// }}}
+
+/// Todos of the current list that bulk operations act on, as indices of the restricted list.
+/// Marked todos are toggled one by one, the visual range goes from its anchor to the current
+/// index, like the visual mode of vim.
+#[derive(Debug, Default, Clone)]
+pub(super) struct Selection {
+    marked: BTreeSet<usize>,
+    anchor: Option<usize>,
+}
+
+impl Selection {
+    #[inline]
+    pub(super) fn is_empty(&self) -> bool {
+        self.marked.is_empty() && self.anchor.is_none()
+    }
+
+    #[inline]
+    pub(super) fn is_visual(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    #[inline]
+    pub(super) fn toggle_mark(&mut self, index: usize) {
+        if !self.marked.remove(&index) {
+            self.marked.insert(index);
+        }
+    }
+
+    #[inline]
+    pub(super) fn toggle_visual(&mut self, index: usize) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(index),
+        };
+    }
+
+    #[inline]
+    pub(super) fn clear(&mut self) {
+        self.marked.clear();
+        self.anchor = None;
+    }
+
+    #[inline]
+    pub(super) fn contains(&self, index: usize, current: usize) -> bool {
+        self.marked.contains(&index)
+            || self
+                .anchor
+                .is_some_and(|anchor| anchor.min(current) <= index && index <= anchor.max(current))
+    }
+
+    /// Sorted indices that are selected, len is the length of the restricted list
+    pub(super) fn indices(&self, current: usize, len: usize) -> Vec<usize> {
+        let mut indices = self.marked.clone();
+        if let Some(anchor) = self.anchor {
+            indices.extend(anchor.min(current)..=anchor.max(current));
+        }
+        indices.into_iter().filter(|&index| index < len).collect()
+    }
+}
@@ -0,0 +1,78 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use crate::date;
+use std::fs;
+use std::path::{Path, PathBuf};
+// }}}
+
+/// Templates are kept as todo files (with their own notes directory) in
+/// `templates/<name>/todo`, next to the todo file.
+#[inline]
+pub fn path(todo_path: &Path, name: &str) -> PathBuf {
+    templates_dir(todo_path).join(name).join("todo")
+}
+
+/// Names are directories of the templates directory, they can't be paths
+#[inline]
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', '\\']) && name != "." && name != ".."
+}
+
+#[inline]
+fn templates_dir(todo_path: &Path) -> PathBuf {
+    todo_path.parent().unwrap().join("templates")
+}
+
+pub fn names(todo_path: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(templates_dir(todo_path))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().join("todo").is_file())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Parses `key=value` variables for the placeholders
+#[inline]
+pub fn parse_vars<'a>(vars: impl IntoIterator<Item = &'a str>) -> Vec<(String, String)> {
+    vars.into_iter()
+        .filter_map(|var| var.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Replaces `{{key}}` placeholders with the values of vars, `{{date}}` is today if it's not
+/// given. Unknown placeholders are kept as they are.
+pub fn fill_placeholders(text: &str, vars: &[(String, String)]) -> String {
+    let mut text = text.to_string();
+    for (key, value) in vars {
+        text = text.replace(&format!("{{{{{key}}}}}"), value);
+    }
+    text.replace("{{date}}", &date::format(Some(date::current())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("release"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../release"));
+        assert!(!is_valid_name(".."));
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        let vars = parse_vars(["version=1.8", "broken"]);
+        assert_eq!(fill_placeholders("Release {{version}} {{other}}", &vars), "Release 1.8 {{other}}");
+        let today = date::format(Some(date::current()));
+        assert_eq!(fill_placeholders("on {{date}}", &vars), format!("on {today}"));
+    }
+}
@@ -0,0 +1,132 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use super::Todo;
+use clap::ValueEnum;
+use std::iter::Peekable;
+// }}}
+
+/// How todos are written as text, like for the system clipboard
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum TextFormat {
+    /// Messages of the todos
+    #[default]
+    Message,
+    /// Lines of the todo file, with priorities, schedules and attributes
+    Todo,
+    /// Messages of the todos and their todo lists, indented
+    Text,
+    /// Markdown task lists of the todos and their todo lists
+    Markdown,
+}
+
+const INDENT: &str = "  ";
+
+/// Writes todos as text, todo lists are only written by the text and markdown formats
+pub fn render(todos: &[Todo], format: TextFormat) -> String {
+    let mut text = String::new();
+    render_level(todos, format, 0, &mut text);
+    text
+}
+
+fn render_level(todos: &[Todo], format: TextFormat, depth: usize, text: &mut String) {
+    for todo in todos {
+        let indent = INDENT.repeat(depth);
+        let line = match format {
+            TextFormat::Message => todo.message.clone(),
+            TextFormat::Todo => String::from(todo),
+            TextFormat::Text => format!("{indent}{}", todo.message),
+            TextFormat::Markdown => {
+                let check = if todo.done() { "x" } else { " " };
+                format!("{indent}- [{check}] {}", todo.message)
+            }
+        };
+        text.push_str(&line);
+        text.push('\n');
+        if matches!(format, TextFormat::Text | TextFormat::Markdown) {
+            if let Some(todo_list) = todo.dependency.as_ref().and_then(|dep| dep.todo_list()) {
+                render_level(&todo_list.todos, format, depth + 1, text);
+            }
+        }
+    }
+}
+
+/// Reads todos from the lines of text. Lines can be lines of a todo file, markdown list items
+/// (`- [x] done`) or plain messages. Indented lines go to the todo list of the line above.
+pub fn parse(text: &str) -> Vec<Todo> {
+    let mut lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| (indent_width(line), parse_line(line.trim())))
+        .peekable();
+    let mut todos = vec![];
+    while lines.peek().is_some() {
+        todos.append(&mut parse_level(&mut lines));
+    }
+    todos
+}
+
+fn parse_level(lines: &mut Peekable<impl Iterator<Item = (usize, Todo)>>) -> Vec<Todo> {
+    let mut todos: Vec<Todo> = vec![];
+    let Some(&(depth, _)) = lines.peek() else {
+        return todos;
+    };
+    while let Some(&(line_depth, _)) = lines.peek() {
+        if line_depth < depth {
+            break;
+        }
+        if line_depth > depth {
+            let mut children = parse_level(lines);
+            let parent = todos.last_mut().unwrap();
+            parent.add_todo_dependency();
+            parent.dependency.as_mut().unwrap().todo_list_mut().todos.append(&mut children);
+            continue;
+        }
+        todos.extend(lines.next().map(|(_, todo)| todo));
+    }
+    todos
+}
+
+#[inline]
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { INDENT.len() } else { 1 })
+        .sum()
+}
+
+fn parse_line(line: &str) -> Todo {
+    for (prefix, done) in [("- [ ] ", false), ("- [x] ", true), ("- [X] ", true), ("- ", false), ("* ", false)] {
+        if let Some(message) = line.strip_prefix(prefix) {
+            let mut todo = Todo::new(message.to_string(), 0);
+            todo.set_done(done);
+            return todo;
+        }
+    }
+    match line.parse::<Todo>() {
+        // The files of its dependency aren't copied with the line
+        Ok(mut todo) if line.starts_with('[') => {
+            todo.dependency = None;
+            todo
+        }
+        _ => Todo::new(line.to_string(), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let todos = parse("- [ ] Release\n  - [x] Changelog\n  - [ ] Tag\n    Push\n[2] Rest {id:1}\n\nplain");
+        assert_eq!(todos.len(), 3);
+        assert_eq!(todos[1].priority(), 2);
+        assert!(todos[1].dependency.is_none());
+        assert_eq!(todos[2].message, "plain");
+        let markdown = render(&todos, TextFormat::Markdown);
+        assert!(markdown.starts_with("- [ ] Release\n  - [x] Changelog\n  - [ ] Tag\n    - [ ] Push\n"));
+        assert_eq!(render(&todos, TextFormat::Message), "Release\nRest\nplain\n");
+        assert_eq!(parse(&markdown)[0], todos[0]);
+        assert_eq!(parse(&render(&todos, TextFormat::Todo))[1], todos[1]);
+    }
+}
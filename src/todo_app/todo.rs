@@ -1,16 +1,36 @@
 // vim:fileencoding=utf-8:foldmethod=marker
 //imports {{{
-use std::{fmt, fs, io, path::Path, str::FromStr};
+use std::{cell::RefCell, collections::{HashMap, HashSet}, fmt, fs, io, path::Path, rc::Rc, str::FromStr};
+pub mod attributes;
 mod dependency;
+pub mod history;
 mod note;
 pub mod schedule;
-use super::TodoList;
-use crate::{DisplayArgs, TodoDisplay};
+use super::{template, TodoList};
+use crate::{date::{self, Calendar, DisplayFormat}, DisplayArgs, ProgressFormat, TodoDisplay};
+use attributes::Attributes;
 use dependency::Dependency;
+pub(super) use dependency::{edit_list, LoadedLists};
 use note::{open_note_temp_editor, sha1};
 use schedule::Schedule;
 // }}}
 
+/// A `<sha1>.<extension>` file name made from seed and the current time, that isn't in names
+fn fresh_name(seed: &str, extension: &str, names: &mut HashSet<String>) -> String {
+    let now = date::now();
+    (0..)
+        .map(|n| format!("{}.{extension}", sha1(&format!("{seed} {now} {n}"))))
+        .find(|name| names.insert(name.clone()))
+        .unwrap()
+}
+
+/// Attribute keys of the creation and completion dates
+pub const CREATED: &str = "created";
+pub const COMPLETED: &str = "completed";
+/// Attribute keys of stable todo ids, and the ids of todos that block a todo
+pub const ID: &str = "id";
+pub const BLOCKED_BY: &str = "blocked-by";
+
 #[derive(Debug, Eq, PartialEq, Clone, Default)]
 pub struct Todo {
     pub message: String,
@@ -20,22 +40,13 @@ pub struct Todo {
     last_schedule: Option<Schedule>,
     done: bool,
     pub schedule: Option<Schedule>,
+    attributes: Attributes,
 }
 
 impl fmt::Display for Todo {
     #[inline(always)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let shcedule_str = self
-            .schedule
-            .as_ref()
-            .map(|schedule| schedule.display())
-            .unwrap_or_default();
-        let note_string = self.dependency.as_ref().map_or(".", |dep| dep.display());
-        let Todo {
-            priority, message, ..
-        } = self;
-
-        write!(f, "{priority}{note_string} {message}{shcedule_str}")
+        f.write_str(&self.display_with_date_format(&DisplayFormat::Default, Calendar::default()))
     }
 }
 
@@ -51,7 +62,13 @@ impl TodoDisplay for Todo {
         } else {
             ""
         };
-        format!("{done_string}{self}")
+        let progress_string = self.display_progress(&args.progress);
+        let todo_string = self.display_with_date_format(&args.date_format, args.calendar);
+        if args.show_timestamps {
+            format!("{done_string}{todo_string}{progress_string}{}", self.display_timestamps(&args.date_format, args.calendar))
+        } else {
+            format!("{done_string}{todo_string}{progress_string}")
+        }
     }
 }
 
@@ -79,8 +96,8 @@ impl From<&Todo> for String {
         let schedule_str: String = todo.schedule.as_ref().map(|x| x.into()).unwrap_or_default();
 
         format!(
-            "[{done_str}{}]{dep_str} {}{schedule_str}",
-            todo.priority, todo.message
+            "[{done_str}{}]{dep_str} {}{}{schedule_str}",
+            todo.priority, todo.message, todo.attributes.block()
         )
     }
 }
@@ -154,9 +171,15 @@ impl FromStr for Todo {
         if state == State::Message && !message.is_empty() {
             let schedule: Option<Schedule> = schedule_string.parse().ok();
             let dependency = dependency_string.parse().ok();
+            let (message, mut attributes) = Attributes::split_message(message);
 
             if let Some(schedule) = schedule.as_ref() {
                 done = schedule.date_should_be_done();
+                // A scheduled todo that recurred isn't completed anymore, its history keeps when
+                // it was
+                if !done && !schedule.is_reminder() {
+                    attributes.remove(COMPLETED);
+                }
             }
             Ok(Todo {
                 dependency,
@@ -164,6 +187,7 @@ impl FromStr for Todo {
                 message,
                 priority,
                 done,
+                attributes,
                 ..Default::default()
             })
         } else {
@@ -172,19 +196,222 @@ impl FromStr for Todo {
     }
 }
 
+#[inline]
+pub fn is_tag(word: &str) -> bool {
+    word.len() > 1 && (word.starts_with('#') || word.starts_with('@')) && !word.contains(char::is_whitespace)
+}
+
 impl Todo {
+    /// A todo of message, whose trailing attributes block (like `{owner:bob}`) is split into
+    /// its attributes
     #[inline]
     pub fn new(message: String, priority: u8) -> Self {
+        let (message, attributes) = Attributes::split_message(message);
         Todo {
             message,
             priority: Self::fixed_priority(priority),
+            attributes,
             ..Default::default()
         }
     }
 
+    #[inline]
+    pub fn created(&self) -> Option<date::Type> {
+        self.attribute(CREATED).and_then(|created| date::parse(created).ok())
+    }
+
+    /// Records today as the creation date, unless the todo already has one
+    #[inline]
+    pub fn ensure_created(&mut self) {
+        if self.created().is_none() {
+            self.attributes.set(CREATED, date::format(Some(date::current())));
+        }
+    }
+
+    #[inline]
+    pub fn completed(&self) -> Option<date::Type> {
+        self.attribute(COMPLETED).and_then(|completed| date::parse(completed).ok())
+    }
+
+    /// Whether the todo is a due reminder (see `Schedule::is_due`) that isn't done yet. Reminders
+    /// of the days before are always read as done, they're only done if completed since their day.
+    pub fn is_due_reminder(&self, now: date::DateTime) -> bool {
+        let Some(day) = self.schedule.as_ref().filter(|sch| sch.is_due(now)).and_then(|sch| sch.date()) else {
+            return false;
+        };
+        !(day == now.date() && self.done) && self.completed().is_none_or(|completed| completed < day)
+    }
+
+    /// Done todos that aren't scheduled can be archived, the ones completed before `before` if
+    /// it's given. Todos without a completion date are considered old.
+    #[inline]
+    pub fn is_archivable(&self, before: Option<date::Type>) -> bool {
+        self.done
+            && self.schedule.is_none()
+            && before.is_none_or(|before| self.completed().is_none_or(|completed| completed < before))
+    }
+
+    /// Done todos that were completed before their completion dates were kept, are given today
+    #[inline]
+    pub fn ensure_completed(&mut self) {
+        if self.done {
+            self.ensure_completed_today();
+        }
+    }
+
+    #[inline]
+    fn ensure_completed_today(&mut self) {
+        if self.completed().is_none() {
+            self.attributes.set(COMPLETED, date::format(Some(date::current())));
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.attribute(ID)
+    }
+
+    /// Returns the id of the todo, and gives it one if it doesn't have any
+    pub fn ensure_id(&mut self) -> String {
+        if let Some(id) = self.id() {
+            return id.to_string();
+        }
+        let id = sha1(&format!("{} {}", self.message, date::now()))[..8].to_string();
+        self.attributes.set(ID, id.clone());
+        id
+    }
+
+    #[inline]
+    pub fn blocked_by(&self) -> impl Iterator<Item = &str> {
+        self.attribute(BLOCKED_BY)
+            .into_iter()
+            .flat_map(|ids| ids.split(','))
+    }
+
+    pub fn block_by(&mut self, id: &str) {
+        if !self.blocked_by().any(|blocker| blocker == id) {
+            let ids: Vec<&str> = self.blocked_by().chain([id]).collect();
+            self.attributes.set(BLOCKED_BY, ids.join(","));
+        }
+    }
+
+    pub fn unblock(&mut self, id: &str) {
+        let ids: Vec<&str> = self.blocked_by().filter(|&blocker| blocker != id).collect();
+        if ids.is_empty() {
+            self.attributes.remove(BLOCKED_BY);
+        } else {
+            self.attributes.set(BLOCKED_BY, ids.join(","));
+        }
+    }
+
+    /// `undone_ids` are ids of todos that aren't done yet, see TodoList::undone_ids
+    #[inline]
+    pub fn is_blocked(&self, undone_ids: &HashSet<String>) -> bool {
+        !self.done() && self.blocked_by().any(|id| undone_ids.contains(id))
+    }
+
+    #[inline]
+    fn display_progress(&self, format: &ProgressFormat) -> String {
+        let Some((done, total)) = self
+            .dependency
+            .as_ref()
+            .filter(|dep| dep.is_list())
+            .map(Dependency::progress)
+            .filter(|&(_, total)| total > 0)
+        else {
+            return String::new();
+        };
+        const BAR_WIDTH: usize = 10;
+        match format {
+            ProgressFormat::None => String::new(),
+            ProgressFormat::Count => format!(" [{done}/{total}]"),
+            ProgressFormat::Percent => format!(" [{}%]", done * 100 / total),
+            ProgressFormat::Bar => {
+                let filled = done * BAR_WIDTH / total;
+                format!(" [{}{}]", "#".repeat(filled), ".".repeat(BAR_WIDTH - filled))
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn display_with_date_format(&self, format: &DisplayFormat, calendar: Calendar) -> String {
+        let shcedule_str = self
+            .schedule
+            .as_ref()
+            .map(|schedule| schedule.display(format, calendar))
+            .unwrap_or_default();
+        let note_string = self.dependency.as_ref().map_or(".", |dep| dep.display());
+        let Todo {
+            priority, message, attributes, ..
+        } = self;
+        let attributes_str = attributes.block_without(&[CREATED, COMPLETED]);
+
+        format!("{priority}{note_string} {message}{attributes_str}{shcedule_str}")
+    }
+
+    #[inline]
+    fn display_timestamps(&self, format: &DisplayFormat, calendar: Calendar) -> String {
+        let mut timestamps = vec![];
+        if let Some(created) = self.created() {
+            timestamps.push(format!("created {}", date::display(Some(created), format, calendar)));
+        }
+        if let Some(completed) = self.completed() {
+            timestamps.push(format!("completed {}", date::display(Some(completed), format, calendar)));
+        }
+        if timestamps.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", timestamps.join(", "))
+        }
+    }
+
     #[inline]
     pub fn matches(&self, query: &str) -> bool {
-        self.message.contains(query) || self.message.to_lowercase().contains(query)
+        self.message.contains(query) || self.message.to_lowercase().contains(query) || self.attributes.matches(query)
+    }
+
+    #[inline]
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    #[inline]
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key)
+    }
+
+    #[inline]
+    pub fn set_attributes(&mut self, attributes: Attributes) {
+        self.attributes = attributes;
+    }
+
+    /// Sets the message, attributes of its trailing attributes block are set on the todo
+    #[inline]
+    pub fn set_message(&mut self, message: String) {
+        let (message, attributes) = Attributes::split_message(message);
+        for (key, value) in attributes.iter() {
+            self.attributes.set(key, value.to_string());
+        }
+        self.message = message;
+    }
+
+    /// `#tag` and `@context` words of the message
+    #[inline]
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.message
+            .split_whitespace()
+            .map(|word| word.trim_end_matches(['.', ',', ';', ':', '!', '?']))
+            .filter(|word| is_tag(word))
+    }
+
+    /// Tags can be given with their `#`/`@` sign or without it, to match both kinds
+    #[inline]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        if is_tag(tag) {
+            self.tags().any(|word| word.eq_ignore_ascii_case(tag))
+        } else {
+            self.tags().any(|word| word[1..].eq_ignore_ascii_case(tag))
+        }
     }
 
     #[inline]
@@ -225,19 +452,27 @@ impl Todo {
         }
     }
 
+    /// Files that are still referenced in `refs` (see TodoList::dependency_refs) are kept
     #[inline]
-    pub fn delete_dependency_file(&mut self, path: &Path) -> io::Result<()> {
-        if let Some(dependency) = &mut self.dependency {
-            dependency.todo_list.remove_dependency_files(path)?;
+    pub fn delete_dependency_file(&self, path: &Path, refs: &HashMap<String, usize>) -> io::Result<()> {
+        if let Some(schedule) = &self.schedule {
+            schedule.delete_history_file(path);
+        }
+        if let Some(dependency) = self.dependency.as_ref().filter(|dep| !refs.contains_key(dep.name())) {
+            if let Some(todo_list) = dependency.todo_list() {
+                todo_list.remove_dependency_files(path, refs)?;
+            }
             let _ = fs::remove_file(path.join(dependency.name()));
         }
         Ok(())
     }
 
     #[inline]
-    pub fn delete_removed_dependent_files(&mut self, path: &Path) -> io::Result<()> {
-        if let Some(dependency) = &mut self.removed_dependency {
-            let _ = dependency.todo_list.remove_dependency_files(path);
+    pub fn delete_removed_dependent_files(&self, path: &Path, refs: &HashMap<String, usize>) -> io::Result<()> {
+        if let Some(dependency) = self.removed_dependency.as_ref().filter(|dep| !refs.contains_key(dep.name())) {
+            if let Some(todo_list) = dependency.todo_list() {
+                let _ = todo_list.remove_dependency_files(path, refs);
+            }
             let _ = fs::remove_file(path.join(dependency.name()));
         }
         Ok(())
@@ -278,7 +513,12 @@ impl Todo {
 
     #[inline]
     pub fn hash(&self) -> String {
-        sha1(&format!("{} {}", self.priority, self.message))
+        Self::hash_of(self.priority, &self.message)
+    }
+
+    #[inline]
+    fn hash_of(priority: u8, message: &str) -> String {
+        sha1(&format!("{priority} {message}"))
     }
 
     #[inline]
@@ -317,11 +557,106 @@ impl Todo {
     #[inline]
     pub fn set_done(&mut self, done: bool) {
         if let Some(schedule) = self.schedule.as_mut() {
+            if done && !self.done {
+                schedule.complete(Self::hash_of(self.priority, &self.message));
+            } else if !done && self.done {
+                schedule.uncomplete();
+            }
             schedule.set_current_date();
         }
+        // Reminders of the days before are read by when they were completed, see is_due_reminder
+        if done && !self.done && self.schedule.as_ref().is_some_and(Schedule::is_reminder) {
+            self.ensure_completed_today();
+        } else if !done {
+            self.attributes.remove(COMPLETED);
+        }
         self.done = done;
     }
 
+    /// Gives the history and dependency lists of the todo (and of its whole tree) new file names
+    /// that aren't in names, so a copy of a todo doesn't share the files of the original one.
+    /// Notes are named by their content, they keep their names.
+    pub fn renew_file_names(&mut self, names: &mut HashSet<String>) {
+        let hash = self.hash();
+        if let Some(schedule) = self.schedule.as_mut() {
+            schedule.rename_history(fresh_name(&hash, "history", names));
+        }
+        if let Some(dependency) = self.dependency.as_mut() {
+            dependency.renew_file_names(&hash, names);
+        }
+    }
+
+    /// Like renew_file_names, but the dependency shares list (the list of the tree with its file
+    /// name) instead of getting new file names
+    pub(crate) fn link_dependency(&mut self, list: Rc<RefCell<TodoList>>, names: &mut HashSet<String>) {
+        let hash = self.hash();
+        if let Some(schedule) = self.schedule.as_mut() {
+            schedule.rename_history(fresh_name(&hash, "history", names));
+        }
+        if let Some(dependency) = self.dependency.as_mut() {
+            dependency.share_list(list);
+        }
+    }
+
+    /// A todo with the message and priority of this one that holds todos in a list of its own,
+    /// like the archived todos of its list. The list gets a file name that isn't in names.
+    pub(crate) fn parent_copy(&self, todos: Vec<Todo>, names: &mut HashSet<String>) -> Todo {
+        let mut todo_list = TodoList::new();
+        todo_list.todos = todos;
+        let name = fresh_name(&self.hash(), "todo", names);
+        Todo {
+            message: self.message.clone(),
+            priority: self.priority,
+            dependency: Some(Dependency::with_list(name, todo_list)),
+            ..Default::default()
+        }
+    }
+
+    /// Makes the todo a fresh copy of a template todo: placeholders are filled, it's undone (and
+    /// created today if record_created), and its dependencies get their own files that aren't in
+    /// names
+    pub fn instantiate(&mut self, vars: &[(String, String)], record_created: bool, names: &mut HashSet<String>) {
+        self.message = template::fill_placeholders(&self.message, vars);
+        self.done = false;
+        for key in [ID, BLOCKED_BY, COMPLETED, CREATED] {
+            self.attributes.remove(key);
+        }
+        if record_created {
+            self.ensure_created();
+        }
+        if let Some(schedule) = self.schedule.as_mut() {
+            schedule.clear_history();
+        }
+        let hash = self.hash();
+        if let Some(dependency) = self.dependency.as_mut() {
+            dependency.instantiate(&hash, vars, record_created, names);
+        }
+    }
+
+    /// Marks every todo of the dependency list (and their dependencies) done or undone
+    pub fn set_dependencies_done(&mut self, done: bool, record_completed: bool) {
+        if let Some(dependency) = self.dependency.as_mut().filter(|dep| dep.is_list()) {
+            dependency.todo_list_mut().set_all_done(done, record_completed);
+        }
+    }
+
+    /// Todos of the dependency list that were done before the current period of the schedule
+    /// are undone, if the todo has recurred. `always` is for when the schedule doesn't reset
+    /// its dependencies itself.
+    pub fn reset_recurred_dependencies(&mut self, always: bool) {
+        let Some(period_start) = self
+            .schedule
+            .as_ref()
+            .filter(|schedule| !self.done && (always || schedule.resets_dependencies()))
+            .and_then(Schedule::period_start)
+        else {
+            return;
+        };
+        if let Some(dependency) = self.dependency.as_mut().filter(|dep| dep.is_list()) {
+            dependency.todo_list_mut().undo_done_before(period_start);
+        }
+    }
+
     #[inline]
     pub fn decrease_priority(&mut self) {
         if Self::standardize_priority(self.priority) < 9 {
@@ -382,6 +717,7 @@ mod tests {
     use clap::Parser;
 
     use super::*;
+    use crate::date;
 
     #[test]
     fn test_todo_into_string() {
@@ -408,6 +744,7 @@ mod tests {
             message: "Test".to_string(),
             priority: 1,
             done: false,
+            attributes: Attributes::default(),
         });
 
         let result: Result<Todo, TodoError> = input.to_string().parse();
@@ -551,6 +888,136 @@ mod tests {
         assert_eq!(test.display_with_args(&DisplayArgs::parse()), expected)
     }
 
+    #[test]
+    fn test_schedule_history() {
+        let input = "[2] this one should be daily [D1(2023-09-05)]";
+        let mut todo = Todo::from_str(input).unwrap();
+        todo.set_done(true);
+        let history_name = todo.schedule.as_ref().unwrap().history().unwrap().name().to_string();
+        assert_eq!(history_name, format!("{}.history", todo.hash()));
+        let today = date::format(Some(date::current()));
+        let expected = format!("[-2] this one should be daily [D1({today})>{history_name}]");
+        assert_eq!(String::from(&todo), expected);
+        assert_eq!(Todo::from_str(&expected).unwrap().schedule.unwrap().history().unwrap().name(), history_name);
+        todo.set_done(false);
+        assert!(todo.schedule.unwrap().stats().is_none());
+    }
+
+    #[test]
+    fn test_attributes() {
+        let input = "[1] review the pr {estimate:2h ticket:C3-12} [D1(2023-09-05)]";
+        let todo = Todo::from_str(input).unwrap();
+        assert_eq!(todo.message, "review the pr");
+        assert_eq!(todo.attribute("ticket"), Some("C3-12"));
+        assert!(todo.schedule.is_some());
+        assert!(todo.matches("estimate:2H"));
+        assert!(todo.matches("ticket:"));
+        let input = "[1] review the pr {estimate:2h ticket:C3-12}";
+        assert_eq!(String::from(&Todo::from_str(input).unwrap()), input);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let mut todo = Todo::new("Test".to_string(), 1);
+        assert_eq!(todo.created(), None);
+        todo.ensure_created();
+        assert_eq!(todo.created(), Some(date::current()));
+        assert_eq!(todo.to_string(), "1. Test");
+        todo.toggle_done();
+        assert_eq!(todo.completed(), None);
+        todo.ensure_completed();
+        assert_eq!(todo.completed(), Some(date::current()));
+        todo.toggle_done();
+        assert_eq!(todo.completed(), None);
+    }
+
+    #[test]
+    fn test_checklist_reset() {
+        let days_ago = |days: i64| date::format(Some(date::add_days(date::current(), -days)));
+        let input = format!("[1] checklist [D7*({})]", days_ago(8));
+        let mut todo = Todo::from_str(&input).unwrap();
+        assert_eq!(String::from(&todo), input);
+        assert!(!todo.done());
+        todo.add_todo_dependency();
+        let mut list = todo.dependency.as_mut().unwrap().todo_list_mut();
+        list.push(Todo::from_str(&format!("[-1] old step {{completed:{}}}", days_ago(8))).unwrap());
+        list.push(Todo::from_str(&format!("[-1] new step {{completed:{}}}", days_ago(0))).unwrap());
+        drop(list);
+        todo.reset_recurred_dependencies(false);
+        let list = todo.dependency.as_ref().unwrap().todo_list().unwrap();
+        assert_eq!(list.iter().map(Todo::done).collect::<Vec<_>>(), vec![false, true]);
+    }
+
+    #[test]
+    fn test_blocked_by() {
+        let mut blocker = Todo::new("Blocker".to_string(), 1);
+        let id = blocker.ensure_id();
+        assert_eq!(blocker.ensure_id(), id);
+        let mut todo = Todo::new("Blocked".to_string(), 1);
+        todo.block_by(&id);
+        todo.block_by("other");
+        todo.block_by(&id);
+        assert_eq!(todo.blocked_by().collect::<Vec<_>>(), vec![id.as_str(), "other"]);
+        assert!(todo.is_blocked(&HashSet::from([id.clone()])));
+        assert!(!todo.is_blocked(&HashSet::new()));
+        todo.unblock("other");
+        todo.unblock(&id);
+        assert_eq!(todo.attribute(BLOCKED_BY), None);
+    }
+
+    #[test]
+    fn test_recurred_completion() {
+        let todo = Todo::from_str("[-1] water the plants {completed:2023-09-01} [D1(2023-09-01)]").unwrap();
+        assert!(!todo.done());
+        assert_eq!(todo.completed(), None);
+    }
+
+    #[test]
+    fn test_message_attributes() {
+        let mut todo = Todo::new("fix it {owner:bob}".to_string(), 1);
+        assert_eq!(todo.message, "fix it");
+        assert_eq!(todo.attribute("owner"), Some("bob"));
+        todo.set_message("fix it again {url:x.y}".to_string());
+        assert_eq!(todo.message, "fix it again");
+        assert_eq!(todo.attribute("owner"), Some("bob"));
+        assert_eq!(todo.attribute("url"), Some("x.y"));
+    }
+
+    #[test]
+    fn test_tags() {
+        let todo = Todo::new("call #Work people @home, then #work-out".to_string(), 1);
+        assert_eq!(todo.tags().collect::<Vec<_>>(), vec!["#Work", "@home", "#work-out"]);
+        assert!(todo.has_tag("work"));
+        assert!(todo.has_tag("#work"));
+        assert!(todo.has_tag("home"));
+        assert!(!todo.has_tag("#home"));
+        assert!(!todo.has_tag("people"));
+    }
+
+    #[test]
+    fn test_reminder_time() {
+        let input = "[-1] call mom [R(2023-09-05 14:30)]";
+        let todo = Todo::from_str(input).unwrap();
+        assert_eq!(String::from(&todo), input);
+        let schedule = todo.schedule.unwrap();
+        let at = |time| date::parse("2023-09-05").unwrap().and_time(date::parse_time(time).unwrap());
+        assert!(!schedule.is_due(at("14:29")));
+        assert!(schedule.is_due(at("14:30")));
+        let next_day = date::parse("2023-09-06").unwrap().and_time(date::parse_time("09:00").unwrap());
+        assert!(schedule.is_due(next_day));
+    }
+
+    #[test]
+    fn test_is_due_reminder() {
+        let now = date::parse("2023-09-06").unwrap().and_time(date::parse_time("09:00").unwrap());
+        let overdue = Todo::from_str("[1] call mom [R(2023-09-05)]").unwrap();
+        assert!(overdue.is_due_reminder(now));
+        let completed = Todo::from_str("[-1] call mom [R(2023-09-05)] {completed:2023-09-05}").unwrap();
+        assert!(!completed.is_due_reminder(now));
+        let upcoming = Todo::from_str("[1] call mom [R(2023-09-07)]").unwrap();
+        assert!(!upcoming.is_due_reminder(now));
+    }
+
     #[test]
     fn test_weekly() {
         let input = "[-2] this one should be daily [D7(2023-09-05)]";
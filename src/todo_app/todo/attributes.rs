@@ -0,0 +1,151 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+// }}}
+
+/// User defined `key:value` attributes of a todo. They're written as ` {key:value key:value}`
+/// after the message, which calcurse just keeps as a part of the message.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Attributes(BTreeMap<String, String>);
+
+#[derive(Debug, PartialEq)]
+pub struct AttributesMalformed;
+
+impl FromStr for Attributes {
+    type Err = AttributesMalformed;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut map = BTreeMap::new();
+        for word in s.split_whitespace() {
+            match word.split_once(':') {
+                Some((key, value)) if Self::is_valid_key(key) && !value.is_empty() && !value.contains(['{', '}']) => {
+                    map.insert(key.to_string(), value.to_string());
+                }
+                _ => return Err(AttributesMalformed),
+            }
+        }
+        Ok(Self(map))
+    }
+}
+
+impl fmt::Display for Attributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self.0.iter().map(|(key, value)| format!("{key}:{value}")).collect();
+        write!(f, "{}", pairs.join(" "))
+    }
+}
+
+impl Attributes {
+    #[inline]
+    fn is_valid_key(key: &str) -> bool {
+        !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    }
+
+    /// Splits the trailing attributes block of a message, the message is kept as is if it
+    /// doesn't end with a valid one
+    pub fn split_message(message: String) -> (String, Self) {
+        if message.ends_with('}') {
+            if let Some(start) = message.rfind(" {") {
+                if let Ok(attributes) = message[start + 2..message.len() - 1].parse::<Self>() {
+                    if !attributes.is_empty() {
+                        return (message[..start].to_string(), attributes);
+                    }
+                }
+            }
+        }
+        (message, Self::default())
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    #[inline]
+    pub fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), value);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.0.remove(key)
+    }
+
+    /// The block that is written after the message, empty if there are no attributes
+    #[inline]
+    pub fn block(&self) -> String {
+        self.block_without(&[])
+    }
+
+    /// Like block, without the `hidden` keys
+    pub fn block_without(&self, hidden: &[&str]) -> String {
+        let pairs: Vec<String> = self
+            .iter()
+            .filter(|(key, _)| !hidden.contains(key))
+            .map(|(key, value)| format!("{key}:{value}"))
+            .collect();
+        if pairs.is_empty() {
+            String::new()
+        } else {
+            format!(" {{{}}}", pairs.join(" "))
+        }
+    }
+
+    /// Matches `key:value` queries, `key:` matches every todo that has the key
+    pub fn matches(&self, query: &str) -> bool {
+        match query.split_once(':') {
+            Some((key, "")) => self.0.contains_key(key),
+            Some((key, value)) => self.get(key).is_some_and(|val| val.eq_ignore_ascii_case(value)),
+            None => false,
+        }
+    }
+}
+
+/// Compares values by their leading numbers (so `2h` < `10h`) and then as strings
+pub fn compare_values(a: &str, b: &str) -> Ordering {
+    let number = |value: &str| -> Option<f64> {
+        let end = value
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(value.len());
+        value[..end].parse().ok()
+    };
+    match (number(a), number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y).then_with(|| a.cmp(b)),
+        _ => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_message() {
+        let (message, attributes) = Attributes::split_message("fix it {owner:bob url:https://x.y/1}".to_string());
+        assert_eq!(message, "fix it");
+        assert_eq!(attributes.get("url"), Some("https://x.y/1"));
+        assert_eq!(attributes.block(), " {owner:bob url:https://x.y/1}");
+        let (message, attributes) = Attributes::split_message("set {a, b}".to_string());
+        assert_eq!(message, "set {a, b}");
+        assert!(attributes.is_empty());
+    }
+
+    #[test]
+    fn test_compare_values() {
+        assert_eq!(compare_values("2h", "10h"), Ordering::Less);
+        assert_eq!(compare_values("bob", "alice"), Ordering::Greater);
+    }
+}
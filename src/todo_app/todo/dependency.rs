@@ -1,13 +1,19 @@
 // vim:fileencoding=utf-8:foldmethod=marker
 // imports {{{
-use crate::todo_app::todo_list::TodoCmp;
+use crate::todo_app::todo_list::{ChangeStamp, TodoCmp};
 
+use super::fresh_name;
+use super::note::sha1;
 use super::TodoList;
+use crate::todo_app::template;
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::{
     fs::File,
     io::{self, Write},
     path::Path,
+    rc::Rc,
 };
 //}}}
 
@@ -18,15 +24,45 @@ enum DependencyMode {
     Note,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Dependency {
     name: String,
     mode: DependencyMode,
     note: String,
     written: bool,
-    pub(crate) todo_list: TodoList,
+    shared: Cell<bool>,
+    /// The progress and the changes of the tree it was counted at, see ListChanges
+    progress: RefCell<Option<(ChangeStamp, (usize, usize))>>,
+    /// The same list for every dependency of the tree with this name, see TodoList::read_dependencies
+    todo_list: Rc<RefCell<TodoList>>,
+}
+
+impl PartialEq for Dependency {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.mode == other.mode
+            && self.note == other.note
+            && self.written == other.written
+            && self.shared == other.shared
+            && self.todo_list == other.todo_list
+    }
 }
 
+impl Eq for Dependency {}
+
+/// Borrows a list of the tree to change it, every list should be changed through this so the
+/// change is counted (see ListChanges)
+#[inline]
+pub(crate) fn edit_list(list: &RefCell<TodoList>) -> RefMut<'_, TodoList> {
+    let list = list.borrow_mut();
+    list.count_change();
+    list
+}
+
+/// Lists of the tree by their file names, so a list that more than one todo references is
+/// only loaded once
+pub(crate) type LoadedLists = HashMap<String, Rc<RefCell<TodoList>>>;
+
 impl Dependency {
     #[inline]
     pub fn new_todo_list(hash: String) -> Self {
@@ -47,6 +83,17 @@ impl Dependency {
         }
     }
 
+    /// A dependency that's the file name of list, which isn't written yet
+    #[inline]
+    pub(crate) fn with_list(name: String, todo_list: TodoList) -> Self {
+        Self {
+            name,
+            mode: DependencyMode::TodoList,
+            todo_list: Rc::new(RefCell::new(todo_list)),
+            ..Default::default()
+        }
+    }
+
     #[inline]
     pub fn is_written(&self) -> bool {
         self.written
@@ -58,18 +105,118 @@ impl Dependency {
     }
 
     #[inline]
-    pub fn todo_list(&self) -> Option<&TodoList> {
-        self.is_list().then_some(&self.todo_list)
+    pub fn todo_list(&self) -> Option<Ref<'_, TodoList>> {
+        self.is_list().then(|| self.todo_list.borrow())
     }
 
+    #[inline]
+    pub(crate) fn todo_list_mut(&mut self) -> RefMut<'_, TodoList> {
+        edit_list(&self.todo_list)
+    }
+
+    /// Borrows the list to write it, which only marks what's written and isn't counted as a
+    /// change of the list
+    #[inline]
+    pub(crate) fn todo_list_to_write(&self) -> RefMut<'_, TodoList> {
+        self.todo_list.borrow_mut()
+    }
+
+    /// The list that the other dependencies with the same name share
+    #[inline]
+    pub(crate) fn shared_list(&self) -> &Rc<RefCell<TodoList>> {
+        &self.todo_list
+    }
+
+    /// Makes the dependency use list, the list of another dependency with the same name
+    #[inline]
+    pub(crate) fn share_list(&mut self, list: Rc<RefCell<TodoList>>) {
+        self.todo_list = list;
+    }
+
+    /// Gives the dependency a copy of the list of its own, for dependencies that are renamed
+    #[inline]
+    fn unshare_list(&mut self) {
+        let list = self.todo_list.borrow().clone();
+        self.todo_list = Rc::new(RefCell::new(list));
+    }
+
+    /// Whether other todos of the tree reference the same file too
+    #[inline]
+    pub fn is_shared(&self) -> bool {
+        self.shared.get()
+    }
+
+    #[inline]
+    pub(crate) fn set_shared(&self, shared: bool) {
+        self.shared.set(shared);
+    }
+
+    /// Done and total todo count of the list and its dependencies, it's cached until a list of
+    /// the tree changes
+    pub fn progress(&self) -> (usize, usize) {
+        let todo_list = self.todo_list.borrow();
+        if let Some((_, progress)) = self.progress.borrow().as_ref().filter(|(stamp, _)| stamp.is_current(todo_list.changes())) {
+            return *progress;
+        }
+        let mut progress = (0, 0);
+        for todo in todo_list.iter() {
+            todo_list.adopt(todo);
+            progress.0 += todo.done() as usize;
+            progress.1 += 1;
+            if let Some((done, total)) = todo.dependency.as_ref().map(Dependency::progress) {
+                progress.0 += done;
+                progress.1 += total;
+            }
+        }
+        *self.progress.borrow_mut() = todo_list.changes().stamp().map(|stamp| (stamp, progress));
+        progress
+    }
+
+    #[inline]
+    pub(crate) fn mark_unwritten(&mut self) {
+        self.written = false;
+        edit_list(&self.todo_list).mark_unwritten();
+    }
+
+    /// Gives the dependency its own file that isn't in names, named after seed. Notes are named
+    /// by their content.
+    pub(crate) fn instantiate(&mut self, seed: &str, vars: &[(String, String)], record_created: bool, names: &mut HashSet<String>) {
+        match self.mode {
+            DependencyMode::Note => {
+                self.note = template::fill_placeholders(&self.note, vars);
+                self.name = sha1(&self.note);
+            }
+            DependencyMode::TodoList => {
+                self.name = fresh_name(seed, "todo", names);
+                self.unshare_list();
+                edit_list(&self.todo_list).instantiate(vars, record_created, names);
+            }
+        }
+        self.written = false;
+        self.shared.set(false);
+    }
+
+    /// Gives the list (and the lists of its tree) new file names, see Todo::renew_file_names
+    pub(crate) fn renew_file_names(&mut self, seed: &str, names: &mut HashSet<String>) {
+        if self.mode == DependencyMode::TodoList {
+            self.name = fresh_name(seed, "todo", names);
+            self.unshare_list();
+            for todo in edit_list(&self.todo_list).todos.iter_mut() {
+                todo.renew_file_names(names);
+            }
+        }
+        self.shared.set(false);
+        self.mark_unwritten();
+    }
 
     #[inline]
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Reads the note or the list, a list that's in loaded already is shared instead
     #[inline]
-    pub fn read(&mut self, path: &Path, todo_cmp: TodoCmp) -> io::Result<()> {
+    pub fn read(&mut self, path: &Path, todo_cmp: TodoCmp, sort_attribute: &Rc<str>, loaded: &mut LoadedLists) -> io::Result<()> {
         let file_path = path.join(&self.name);
         let name_todo = format!("{}.todo", self.name);
         match self.mode {
@@ -85,11 +232,17 @@ impl Dependency {
                     self.name = name_todo;
                     self.mode = DependencyMode::TodoList;
                 }
-                self.todo_list = TodoList::read(&path.join(&self.name));
-                self.todo_list.set_todo_cmp(todo_cmp);
-                self.todo_list.sort();
-                self.todo_list.changed = false;
-                self.todo_list.read_dependencies(path);
+                if let Some(list) = loaded.get(&self.name) {
+                    self.todo_list = list.clone();
+                } else {
+                    let mut todo_list = TodoList::read(&path.join(&self.name));
+                    todo_list.set_todo_cmp(todo_cmp, sort_attribute.clone());
+                    todo_list.sort();
+                    todo_list.changed = false;
+                    loaded.insert(self.name.clone(), self.todo_list.clone());
+                    let _ = todo_list.read_dependencies_with(path, loaded);
+                    *edit_list(&self.todo_list) = todo_list;
+                }
             }
             _ => {}
         };
@@ -101,8 +254,8 @@ impl Dependency {
     #[inline]
     pub fn write(&mut self, path: &Path) -> io::Result<()> {
         match self.mode {
-            DependencyMode::TodoList => {
-                self.todo_list.write(&path.join(&self.name))?;
+            DependencyMode::TodoList if self.todo_list.borrow().changed => {
+                self.todo_list_to_write().write(&path.join(&self.name))?;
             }
             DependencyMode::Note if !self.written => {
                 self.write_note(path)?;
@@ -124,7 +277,7 @@ impl Dependency {
     pub fn force_write(&self, path: &Path) -> io::Result<()> {
         match self.mode {
             DependencyMode::TodoList => {
-                self.todo_list.force_write(&path.join(&self.name))?;
+                self.todo_list.borrow().force_write(&path.join(&self.name))?;
             }
             DependencyMode::Note => {
                 self.write_note(path)?;
@@ -147,6 +300,7 @@ impl Dependency {
     pub fn display<'a>(&self) -> &'a str {
         match self.mode {
             DependencyMode::Note => ">",
+            DependencyMode::TodoList if self.shared.get() => "=",
             DependencyMode::TodoList => "-",
         }
     }
@@ -0,0 +1,211 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use crate::date;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+// }}}
+
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct History {
+    name: String,
+    dates: Vec<date::Type>,
+    pending: Vec<date::Type>,
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Stats {
+    pub completions: usize,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub rate: f64,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "streak {}, best {}, {:.0}%",
+            self.current_streak,
+            self.longest_streak,
+            self.rate * 100.
+        )
+    }
+}
+
+impl History {
+    #[inline]
+    pub fn new(hash: String) -> Self {
+        Self {
+            name: format!("{hash}.history"),
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    pub fn from_name(name: String) -> Self {
+        Self {
+            name,
+            ..Default::default()
+        }
+    }
+
+    /// Moves the history to a new file, all of its dates are written there
+    #[inline]
+    pub fn rename(&mut self, name: String) {
+        self.name = name;
+        self.pending.clone_from(&self.dates);
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn dates(&self) -> &[date::Type] {
+        &self.dates
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    pub fn add(&mut self, date: date::Type) {
+        if self.dates.last() != Some(&date) {
+            self.dates.push(date);
+            self.pending.push(date);
+        }
+    }
+
+    pub fn remove(&mut self, date: date::Type) {
+        if self.pending.last() == Some(&date) {
+            self.pending.pop();
+            self.dates.pop();
+        }
+    }
+
+    pub fn read(&mut self, path: &Path) -> io::Result<()> {
+        let file_path = path.join(&self.name);
+        if !file_path.is_file() {
+            return Ok(());
+        }
+        let mut dates: Vec<date::Type> = fs::read_to_string(file_path)?
+            .lines()
+            .flat_map(date::parse)
+            .collect();
+        dates.append(&mut self.dates);
+        dates.sort();
+        dates.dedup();
+        self.dates = dates;
+        Ok(())
+    }
+
+    /// Appends the completions that aren't on disk yet to the history file
+    pub fn write(&mut self, path: &Path) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.join(&self.name))?;
+        for date in &self.pending {
+            writeln!(file, "{}", date::format(Some(*date)))?;
+        }
+        self.pending = vec![];
+        Ok(())
+    }
+
+    pub fn force_write(&self, path: &Path) -> io::Result<()> {
+        let mut file = fs::File::create(path.join(&self.name))?;
+        for date in &self.dates {
+            writeln!(file, "{}", date::format(Some(*date)))?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn delete_file(&self, path: &Path) {
+        let _ = fs::remove_file(path.join(&self.name));
+    }
+
+    /// Streaks are runs of completions that are at most `days` apart. The current streak is
+    /// only alive if the last completion is still in its period.
+    pub fn stats(&self, days: i64) -> Stats {
+        let days = days.max(1);
+        let mut longest_streak = 0;
+        let mut streak = 0;
+        let mut last: Option<date::Type> = None;
+        for &date in &self.dates {
+            if date::diff_days(Some(date), last) <= days && last.is_some() {
+                streak += 1;
+            } else {
+                streak = 1;
+            }
+            longest_streak = longest_streak.max(streak);
+            last = Some(date);
+        }
+        let current_streak = if date::diff_days(Some(date::current()), last) <= days {
+            streak
+        } else {
+            0
+        };
+        let rate = match self.dates.first() {
+            Some(&first) => {
+                let periods = date::diff_days(Some(date::current()), Some(first)) / days + 1;
+                (self.dates.len() as f64 / periods as f64).min(1.)
+            }
+            None => 0.,
+        };
+
+        Stats {
+            completions: self.dates.len(),
+            current_streak,
+            longest_streak,
+            rate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days_ago(days: &[i64]) -> History {
+        let mut history = History::new("test".to_string());
+        for &day in days {
+            history.add(date::add_days(date::current(), -day));
+        }
+        history
+    }
+
+    #[test]
+    fn test_daily_streaks() {
+        let history = days_ago(&[9, 8, 7, 6, 3, 2, 1]);
+        let stats = history.stats(1);
+        assert_eq!(stats.completions, 7);
+        assert_eq!(stats.current_streak, 3);
+        assert_eq!(stats.longest_streak, 4);
+        assert_eq!(stats.rate, 0.7);
+    }
+
+    #[test]
+    fn test_broken_streak() {
+        let history = days_ago(&[5, 4]);
+        let stats = history.stats(1);
+        assert_eq!(stats.current_streak, 0);
+        assert_eq!(stats.longest_streak, 2);
+    }
+
+    #[test]
+    fn test_remove_pending() {
+        let mut history = days_ago(&[1]);
+        history.add(date::current());
+        history.remove(date::current());
+        assert_eq!(history.dates().len(), 1);
+        assert_eq!(history.pending.len(), 1);
+    }
+}
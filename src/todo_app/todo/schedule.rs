@@ -1,6 +1,9 @@
+use std::io;
+use std::path::Path;
 use std::str::FromStr;
 
-use crate::date;
+use super::history::{History, Stats};
+use crate::date::{self, Calendar, DisplayFormat};
 
 #[derive(Eq, Debug, PartialEq, Clone, Default)]
 pub enum ScheduleMode {
@@ -13,7 +16,10 @@ pub enum ScheduleMode {
 pub struct Schedule {
     day: i64,
     saved_date: Option<date::Type>,
+    time: Option<date::Time>,
     mode: ScheduleMode,
+    history: Option<History>,
+    resets_dependencies: bool,
 }
 
 #[derive(Default)]
@@ -23,6 +29,7 @@ enum State {
     Days,
     PreDate,
     Date,
+    History,
 }
 
 pub struct NotScheduled;
@@ -34,6 +41,8 @@ impl FromStr for Schedule {
         let mut state = State::default();
         let mut mode = None;
         let mut day_str = String::new();
+        let mut history_name = String::new();
+        let mut resets_dependencies = false;
 
         for c in s.to_string().chars() {
             match state {
@@ -51,6 +60,8 @@ impl FromStr for Schedule {
                 State::Days => {
                     if c.is_ascii_digit() {
                         day_str.push(c);
+                    } else if c == '*' {
+                        resets_dependencies = true;
                     } else if c == '(' {
                         state = State::Date;
                     }
@@ -62,25 +73,34 @@ impl FromStr for Schedule {
                 }
                 State::Date => {
                     if c == ')' {
-                        break;
+                        state = State::History;
                     } else {
                         date_string.push(c)
                     }
                 }
+                State::History => {
+                    if c != '>' {
+                        history_name.push(c)
+                    }
+                }
             }
         }
 
         let day: i64 = day_str.parse().unwrap_or(0);
 
-        let date = match date::parse(&date_string) {
-            Ok(value) => Some(value),
-            Err(_) => None,
+        let (date_string, time) = match date_string.split_once(' ') {
+            Some((date_string, time_string)) => (date_string, date::parse_time(time_string).ok()),
+            None => (date_string.as_str(), None),
         };
+        let date = date::parse(date_string).ok();
         if let Some(mode) = mode {
             Ok(Schedule {
                 day,
                 saved_date: date,
+                time,
                 mode,
+                history: (!history_name.is_empty()).then(|| History::from_name(history_name)),
+                resets_dependencies,
             })
         } else {
             Err(Self::Err {})
@@ -90,11 +110,22 @@ impl FromStr for Schedule {
 
 impl From<&Schedule> for String {
     fn from(schedule: &Schedule) -> String {
-        let date_str = date::format(schedule.saved_date);
+        let mut date_str = date::format(schedule.saved_date);
+        if let Some(time) = schedule.time {
+            date_str = format!("{date_str} {}", date::format_time(time));
+        }
+        let history_str = schedule
+            .history
+            .as_ref()
+            .map(|history| format!(">{}", history.name()))
+            .unwrap_or_default();
 
         match schedule.mode {
             ScheduleMode::Reminder => format!(" [R({date_str})]"),
-            ScheduleMode::Scheduled => format!(" [D{}({date_str})]", schedule.day),
+            ScheduleMode::Scheduled => {
+                let reset_str = if schedule.resets_dependencies { "*" } else { "" };
+                format!(" [D{}{reset_str}({date_str}){history_str}]", schedule.day)
+            }
         }
     }
 }
@@ -115,6 +146,51 @@ impl Schedule {
         }
     }
 
+    /// The first day of the period the schedule is in now, None for reminders
+    #[inline]
+    pub fn period_start(&self) -> Option<date::Type> {
+        match self.mode {
+            ScheduleMode::Scheduled => self.saved_date.map(|date| date::add_days(date, self.day)),
+            ScheduleMode::Reminder => None,
+        }
+    }
+
+    /// Whether the dependency list of the todo is reset to undone on each new period, like a
+    /// checklist. It's written as a `*` after the days.
+    #[inline]
+    pub fn resets_dependencies(&self) -> bool {
+        self.resets_dependencies
+    }
+
+    #[inline]
+    pub fn toggle_resets_dependencies(&mut self) {
+        self.resets_dependencies = !self.resets_dependencies;
+    }
+
+    #[inline]
+    pub fn set_time(&mut self, time: Option<date::Time>) {
+        self.time = time;
+    }
+
+    #[inline]
+    pub fn date(&self) -> Option<date::Type> {
+        self.saved_date
+    }
+
+    #[inline]
+    pub fn time(&self) -> Option<date::Time> {
+        self.time
+    }
+
+    /// Whether this is a reminder for a day before `now`'s, or for `now`'s day and its time (if
+    /// any) has come
+    pub fn is_due(&self, now: date::DateTime) -> bool {
+        self.mode == ScheduleMode::Reminder
+            && self.saved_date.is_some_and(|date| {
+                date < now.date() || date == now.date() && self.time.is_none_or(|time| time <= now.time())
+            })
+    }
+
     #[inline(always)]
     fn current_minus_saved_date(&self) -> i64 {
         date::diff_days(Some(date::current()), self.saved_date)
@@ -140,8 +216,14 @@ impl Schedule {
     }
 
     #[inline(always)]
-    fn display_reminder(&self) -> String {
-        let date_str = date::display(self.saved_date);
+    fn display_reminder(&self, format: &DisplayFormat, calendar: Calendar) -> String {
+        let mut date_str = date::display(self.saved_date, format, calendar);
+        if let Some(time) = self.time {
+            date_str = format!("{date_str} {}", date::format_time(time));
+        }
+        if *format == DisplayFormat::Relative {
+            return format!(" (Reminder {date_str})");
+        }
         match self.saved_minus_current_date() {
             any if any < 0 => format!(" (Reminder for {} [{} days ago])", date_str, -any),
             0 => format!(" (Reminder for today [{}])", date_str),
@@ -151,13 +233,23 @@ impl Schedule {
     }
 
     #[inline(always)]
-    fn display_scheduled(&self) -> String {
-        let inner_str = match self.current_minus_saved_date() {
-            ..=0 => String::new(),
-            1 => String::from(", last done yesterday"),
-            7 => String::from(", last done a week ago"),
-            any if any % 7 == 0 => format!(", last done {} weeks ago", any / 7),
-            any => format!(", last done {} days ago", any),
+    fn display_scheduled(&self, format: &DisplayFormat, calendar: Calendar) -> String {
+        let inner_str = match (self.current_minus_saved_date(), format) {
+            (..=0, _) => String::new(),
+            (1, DisplayFormat::Default) => String::from(", last done yesterday"),
+            (7, DisplayFormat::Default) => String::from(", last done a week ago"),
+            (any, DisplayFormat::Default) if any % 7 == 0 => format!(", last done {} weeks ago", any / 7),
+            (any, DisplayFormat::Default) => format!(", last done {} days ago", any),
+            _ => format!(", last done {}", date::display(self.saved_date, format, calendar)),
+        };
+        let inner_str = match self.stats() {
+            Some(stats) => format!("{inner_str}, {stats}"),
+            None => inner_str,
+        };
+        let inner_str = if self.resets_dependencies {
+            format!("{inner_str}, resets list")
+        } else {
+            inner_str
         };
         match self.day {
             1 => format!(" (Daily{inner_str})"),
@@ -167,10 +259,10 @@ impl Schedule {
         }
     }
 
-    pub fn display(&self) -> String {
+    pub fn display(&self, format: &DisplayFormat, calendar: Calendar) -> String {
         match self.mode {
-            ScheduleMode::Reminder => self.display_reminder(),
-            ScheduleMode::Scheduled => self.display_scheduled(),
+            ScheduleMode::Reminder => self.display_reminder(format, calendar),
+            ScheduleMode::Scheduled => self.display_scheduled(format, calendar),
         }
     }
 
@@ -209,4 +301,73 @@ impl Schedule {
             }
         }
     }
+
+    pub fn complete(&mut self, hash: String) {
+        if self.mode == ScheduleMode::Scheduled {
+            self.history
+                .get_or_insert_with(|| History::new(hash))
+                .add(date::current());
+        }
+    }
+
+    pub fn uncomplete(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.remove(date::current());
+        }
+    }
+
+    #[inline]
+    pub fn rename_history(&mut self, name: String) {
+        if let Some(history) = self.history.as_mut() {
+            history.rename(name);
+        }
+    }
+
+    #[inline]
+    pub fn clear_history(&mut self) {
+        self.history = None;
+    }
+
+    #[inline]
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    pub fn stats(&self) -> Option<Stats> {
+        self.history
+            .as_ref()
+            .filter(|history| !history.is_empty())
+            .map(|history| history.stats(self.days()))
+    }
+
+    #[inline]
+    pub fn read_history(&mut self, path: &Path) -> io::Result<()> {
+        match self.history.as_mut() {
+            Some(history) => history.read(path),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    pub fn write_history(&mut self, path: &Path) -> io::Result<()> {
+        match self.history.as_mut() {
+            Some(history) => history.write(path),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    pub fn force_write_history(&self, path: &Path) -> io::Result<()> {
+        match self.history.as_ref() {
+            Some(history) => history.force_write(path),
+            None => Ok(()),
+        }
+    }
+
+    #[inline]
+    pub fn delete_history_file(&self, path: &Path) {
+        if let Some(history) = self.history.as_ref() {
+            history.delete_file(path);
+        }
+    }
 }
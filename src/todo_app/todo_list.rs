@@ -1,20 +1,27 @@
 // vim:fileencoding=utf-8:foldmethod=marker
 // imports {{{
 use std::cmp;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{read, File};
 use std::io::{self, BufRead, BufWriter, Write};
+use std::cell::{Cell, Ref, RefMut};
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::rc::Rc;
 
-use super::{App, Restriction, SortMethod, Todo};
-use crate::{DisplayArgs, TodoDisplay};
+use super::{todo::LoadedLists, App, Restriction, SortMethod, Todo};
+use crate::{date, DisplayArgs, TodoDisplay};
 //}}}
 
-pub type TodoCmp = fn(&Todo, &Todo) -> cmp::Ordering;
+/// Compares two todos, given the attribute key todos are sorted by (see `SortMethod::Attribute`)
+pub type TodoCmp = fn(&Todo, &Todo, &str) -> cmp::Ordering;
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct TodoList {
     pub todos: Vec<Todo>,
     pub changed: bool,
     pub todo_cmp: TodoCmp,
+    pub sort_attribute: Rc<str>,
+    changes: ListChanges,
 }
 
 impl Default for TodoList {
@@ -23,12 +30,129 @@ impl Default for TodoList {
             todos: Vec::new(),
             changed: false,
             todo_cmp: SortMethod::default().cmp_function(),
+            sort_attribute: Rc::from(""),
+            changes: ListChanges::default(),
         }
     }
 }
 
+/// Counts the changes of the lists of a tree, the lists of a tree share the counter of its root
+/// list. A list can be changed from any of the todos that share it, so a cached progress (or
+/// outline) is only used while no list of its tree has changed since. Lists that aren't in a
+/// tree yet (like new dependency lists) don't count, see TodoList::adopt.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ListChanges(Option<Rc<Cell<usize>>>);
+
+// The counter isn't a part of the list
+impl PartialEq for ListChanges {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ListChanges {}
+
+impl ListChanges {
+    #[inline]
+    fn count(&self) {
+        if let Some(count) = self.0.as_ref() {
+            count.set(count.get().wrapping_add(1));
+        }
+    }
+
+    #[inline]
+    fn is(&self, other: &Rc<Cell<usize>>) -> bool {
+        self.0.as_ref().is_some_and(|count| Rc::ptr_eq(count, other))
+    }
+
+    /// The count to compare with later, lists that don't count have none
+    #[inline]
+    pub(crate) fn stamp(&self) -> Option<ChangeStamp> {
+        self.0.as_ref().map(|count| ChangeStamp(count.clone(), count.get()))
+    }
+}
+
+/// The count of the changes of a tree at some point, see ListChanges
+#[derive(Debug, Clone)]
+pub(crate) struct ChangeStamp(Rc<Cell<usize>>, usize);
+
+impl ChangeStamp {
+    /// Whether changes are the ones of the same tree and it hasn't changed since
+    #[inline]
+    pub(crate) fn is_current(&self, changes: &ListChanges) -> bool {
+        changes.is(&self.0) && self.0.get() == self.1
+    }
+}
+
 type Output = Todo;
 
+/// A value of the tree that's either borrowed from the root list or from the RefCell of a
+/// dependency list
+pub enum Borrowed<'a, T> {
+    Plain(&'a T),
+    Cell(Ref<'a, T>),
+}
+
+impl<T> Deref for Borrowed<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        match self {
+            Self::Plain(value) => value,
+            Self::Cell(value) => value,
+        }
+    }
+}
+
+impl<'a, T> Borrowed<'a, T> {
+    #[inline]
+    pub fn filter_map<U>(self, f: impl FnOnce(&T) -> Option<&U>) -> Option<Borrowed<'a, U>> {
+        match self {
+            Self::Plain(value) => f(value).map(Borrowed::Plain),
+            Self::Cell(value) => Ref::filter_map(value, f).ok().map(Borrowed::Cell),
+        }
+    }
+}
+
+/// Like Borrowed, mutably
+pub enum BorrowedMut<'a, T> {
+    Plain(&'a mut T),
+    Cell(RefMut<'a, T>),
+}
+
+impl<T> Deref for BorrowedMut<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        match self {
+            Self::Plain(value) => value,
+            Self::Cell(value) => value,
+        }
+    }
+}
+
+impl<T> DerefMut for BorrowedMut<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            Self::Plain(value) => value,
+            Self::Cell(value) => value,
+        }
+    }
+}
+
+impl<'a, T> BorrowedMut<'a, T> {
+    #[inline]
+    pub fn filter_map<U>(self, f: impl FnOnce(&mut T) -> Option<&mut U>) -> Option<BorrowedMut<'a, U>> {
+        match self {
+            Self::Plain(value) => f(value).map(BorrowedMut::Plain),
+            Self::Cell(value) => RefMut::filter_map(value, f).ok().map(BorrowedMut::Cell),
+        }
+    }
+}
+
 fn with_index<T, F>(mut f: F) -> impl FnMut(&T) -> bool
 where
     F: FnMut(usize, &T) -> bool,
@@ -44,6 +168,44 @@ impl TodoList {
         }
     }
 
+    /// Makes the list the root of a tree that counts its changes, see ListChanges
+    #[inline]
+    pub(crate) fn count_changes(&mut self) {
+        self.changes = ListChanges(Some(Rc::default()));
+    }
+
+    #[inline]
+    pub(crate) fn changes(&self) -> &ListChanges {
+        &self.changes
+    }
+
+    /// Counts a change of the list for the caches of its tree
+    #[inline]
+    pub(crate) fn count_change(&self) {
+        self.changes.count();
+    }
+
+    /// Makes the lists of the tree of todo count their changes with the counter of this list, for
+    /// todos that are added to it and lists that are walked before caching what's in them
+    pub(crate) fn adopt(&self, todo: &Todo) {
+        let Some(changes) = self.changes.0.as_ref() else {
+            return;
+        };
+        let Some(dependency) = todo.dependency.as_ref().filter(|dep| dep.is_list()) else {
+            return;
+        };
+        let Ok(mut list) = dependency.shared_list().try_borrow_mut() else {
+            return;
+        };
+        if list.changes.is(changes) {
+            return;
+        }
+        list.changes = self.changes.clone();
+        for todo in list.todos.iter() {
+            list.adopt(todo);
+        }
+    }
+
     pub fn index(&self, index: usize, restriction: &Restriction) -> Option<&Output> {
         let size = self.len(restriction);
         let index = index.min(size);
@@ -58,6 +220,7 @@ impl TodoList {
         let size = self.len(restriction);
         let index = index.min(size);
         self.changed = true;
+        self.count_change();
 
         self.todos
             .iter_mut()
@@ -75,25 +238,53 @@ impl TodoList {
         &'a mut self,
         restriction: &'a Restriction,
     ) -> impl Iterator<Item = &mut Todo> {
+        self.count_change();
         self.todos.iter_mut().filter(|todo| restriction(todo))
     }
 
     #[inline]
-    pub(super) fn delete_removed_dependent_files(&mut self, filename: &Path) -> io::Result<()> {
-        for todo in &mut self.todos {
-            if let Some(dependency) = todo.dependency.as_mut() {
-                dependency
-                    .todo_list
-                    .delete_removed_dependent_files(filename)?;
+    pub(super) fn delete_removed_dependent_files(&self, filename: &Path, refs: &HashMap<String, usize>) -> io::Result<()> {
+        for todo in &self.todos {
+            if let Some(todo_list) = todo.dependency.as_ref().and_then(|dependency| dependency.todo_list()) {
+                todo_list.delete_removed_dependent_files(filename, refs)?;
             }
-            todo.delete_removed_dependent_files(filename)?;
+            todo.delete_removed_dependent_files(filename, refs)?;
         }
         Ok(())
     }
 
+    /// Number of todos that reference each dependency file. Dependencies of a shared list
+    /// are only counted once, as it's the same file for all of its parents.
+    pub fn dependency_refs(&self) -> HashMap<String, usize> {
+        let mut refs = HashMap::new();
+        self.count_dependency_refs(&mut refs);
+        refs
+    }
+
+    fn count_dependency_refs(&self, refs: &mut HashMap<String, usize>) {
+        for dependency in self.todos.iter().filter_map(|todo| todo.dependency.as_ref()) {
+            let count = refs.entry(dependency.name().to_string()).or_insert(0);
+            *count += 1;
+            if let Some(todo_list) = dependency.todo_list().filter(|_| *count == 1) {
+                todo_list.count_dependency_refs(refs);
+            }
+        }
+    }
+
+    pub(super) fn mark_shared_dependencies(&self, refs: &HashMap<String, usize>) {
+        for dependency in self.todos.iter().filter_map(|todo| todo.dependency.as_ref()) {
+            dependency.set_shared(refs.get(dependency.name()).is_some_and(|&count| count > 1));
+            if let Some(todo_list) = dependency.todo_list() {
+                todo_list.mark_shared_dependencies(refs);
+            }
+        }
+    }
+
     #[inline]
     pub fn prepend(&mut self, todo: Todo) {
         self.changed = true;
+        self.count_change();
+        self.adopt(&todo);
         self.todos.insert(0, todo);
     }
 
@@ -120,9 +311,9 @@ impl TodoList {
         }
     }
 
-    pub(super) fn remove_dependency_files(&mut self, filename: &Path) -> io::Result<()> {
-        for todo in &mut self.todos {
-            todo.delete_dependency_file(filename)?;
+    pub(super) fn remove_dependency_files(&self, filename: &Path, refs: &HashMap<String, usize>) -> io::Result<()> {
+        for todo in &self.todos {
+            todo.delete_dependency_file(filename, refs)?;
         }
         Ok(())
     }
@@ -142,19 +333,39 @@ impl TodoList {
         }
     }
 
-    pub fn set_todo_cmp(&mut self, sort: TodoCmp) {
+    pub fn set_todo_cmp(&mut self, sort: TodoCmp, sort_attribute: Rc<str>) {
         self.todo_cmp = sort;
+        self.sort_attribute = sort_attribute;
     }
 
+    /// Reads the dependencies of the whole tree, lists that more than one todo references are
+    /// read once and shared by all of them
+    #[inline]
     pub fn read_dependencies(&mut self, folder_name: &Path) -> io::Result<()> {
+        self.read_dependencies_with(folder_name, &mut LoadedLists::new())
+    }
+
+    pub(super) fn read_dependencies_with(&mut self, folder_name: &Path, loaded: &mut LoadedLists) -> io::Result<()> {
+        self.read_histories(folder_name);
         for todo in &mut self.todos {
             if let Some(dependency) = todo.dependency.as_mut() {
-                dependency.read(folder_name, self.todo_cmp)?;
+                dependency.read(folder_name, self.todo_cmp, &self.sort_attribute, loaded)?;
             }
         }
+        for todo in &self.todos {
+            self.adopt(todo);
+        }
         Ok(())
     }
 
+    /// Reads the completion histories of the todos of the list, not of its dependencies
+    #[inline]
+    pub fn read_histories(&mut self, folder_name: &Path) {
+        for schedule in self.todos.iter_mut().filter_map(|todo| todo.schedule.as_mut()) {
+            let _ = schedule.read_history(folder_name);
+        }
+    }
+
     pub fn with_capacity(capacity: usize) -> Self {
         TodoList {
             todos: Vec::with_capacity(capacity),
@@ -175,18 +386,36 @@ impl TodoList {
     pub(super) fn write_dependencies(&mut self, filename: &Path) -> io::Result<()> {
         for todo in &mut self.todos {
             if let Some(dependency) = todo.dependency.as_mut() {
-                dependency.todo_list.write_dependencies(filename)?;
+                dependency.todo_list_to_write().write_dependencies(filename)?;
                 dependency.write(filename)?;
             }
         }
         Ok(())
     }
 
+    #[inline]
+    pub(super) fn write_histories(&mut self, filename: &Path) -> io::Result<()> {
+        for todo in &mut self.todos {
+            if let Some(schedule) = todo.schedule.as_mut() {
+                schedule.write_history(filename)?;
+            }
+            if let Some(dependency) = todo.dependency.as_mut() {
+                dependency.todo_list_to_write().write_histories(filename)?;
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     pub(super) fn force_write_dependencies(&self, filename: &Path) -> io::Result<()> {
         for todo in &self.todos {
+            if let Some(schedule) = todo.schedule.as_ref() {
+                schedule.force_write_history(filename)?;
+            }
             if let Some(dependency) = todo.dependency.as_ref() {
-                dependency.todo_list.force_write_dependencies(filename)?;
+                if let Some(todo_list) = dependency.todo_list() {
+                    todo_list.force_write_dependencies(filename)?;
+                }
                 dependency.force_write(filename)?;
             }
         }
@@ -212,6 +441,7 @@ impl TodoList {
 
     #[inline(always)]
     pub(super) fn retrain_indices(&mut self, sorted_indices: Vec<usize>) {
+        self.count_change();
         self.todos
             .retain(with_index(|i, _| sorted_indices.binary_search(&i).is_err()))
     }
@@ -220,8 +450,194 @@ impl TodoList {
         self.todos.iter()
     }
 
+    /// The first value f gives for the todos of the whole tree, depth first with parents before
+    /// their dependencies. Todos of shared lists are only visited under their first parent.
+    #[inline]
+    pub fn tree_find_map<T>(&self, f: &mut impl FnMut(&Todo) -> Option<T>) -> Option<T> {
+        self.tree_find_map_with(f, &mut HashSet::new())
+    }
+
+    fn tree_find_map_with<T>(&self, f: &mut impl FnMut(&Todo) -> Option<T>, visited: &mut HashSet<String>) -> Option<T> {
+        for todo in self.todos.iter() {
+            if let Some(value) = f(todo) {
+                return Some(value);
+            }
+            let Some(dependency) = todo.dependency.as_ref().filter(|dep| dep.is_list()) else {
+                continue;
+            };
+            if dependency.is_shared() && !visited.insert(dependency.name().to_string()) {
+                continue;
+            }
+            if let Some(value) = dependency.todo_list().and_then(|list| list.tree_find_map_with(f, visited)) {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// Calls f with every todo of the whole tree, in the order of tree_find_map
+    #[inline]
+    pub fn tree_for_each(&self, mut f: impl FnMut(&Todo)) {
+        self.tree_find_map(&mut |todo| {
+            f(todo);
+            None::<()>
+        });
+    }
+
+    /// Values that f gives for the todos of the whole tree, in the order of tree_find_map
+    #[inline]
+    pub fn tree_filter_map<T>(&self, mut f: impl FnMut(&Todo) -> Option<T>) -> Vec<T> {
+        let mut values = vec![];
+        self.tree_for_each(|todo| values.extend(f(todo)));
+        values
+    }
+
+    /// Ids of the todos in the whole tree that aren't done, todos blocked by them are blocked
+    pub fn undone_ids(&self) -> HashSet<String> {
+        self.tree_filter_map(|todo| todo.id().filter(|_| !todo.done()).map(String::from))
+            .into_iter()
+            .collect()
+    }
+
+    /// Names of the dependency and history files of the whole tree
+    pub fn file_names(&self) -> HashSet<String> {
+        let mut names = HashSet::new();
+        self.tree_for_each(|todo| {
+            if let Some(dependency) = todo.dependency.as_ref() {
+                names.insert(dependency.name().to_string());
+            }
+            if let Some(history) = todo.schedule.as_ref().and_then(|schedule| schedule.history()) {
+                names.insert(history.name().to_string());
+            }
+        });
+        names
+    }
+
+    /// Position of the first todo of the tree that predicate matches, the todos of a list are
+    /// searched before its dependencies. It's the tree path (see App::tree_path) of its list followed by its index.
+    pub fn tree_position(&self, predicate: &dyn Fn(&[usize], &Todo) -> bool) -> Option<Vec<usize>> {
+        let mut position = vec![];
+        self.find_tree_position(&mut position, predicate).then_some(position)
+    }
+
+    fn find_tree_position(&self, position: &mut Vec<usize>, predicate: &dyn Fn(&[usize], &Todo) -> bool) -> bool {
+        for (index, todo) in self.todos.iter().enumerate() {
+            position.push(index);
+            if predicate(position, todo) {
+                return true;
+            }
+            position.pop();
+        }
+        for (index, todo) in self.todos.iter().enumerate() {
+            if let Some(todo_list) = todo.dependency.as_ref().and_then(|dep| dep.todo_list()) {
+                position.push(index);
+                if todo_list.find_tree_position(position, predicate) {
+                    return true;
+                }
+                position.pop();
+            }
+        }
+        false
+    }
+
+    /// Makes the list a fresh instance of a template, see Todo::instantiate
+    pub(super) fn instantiate(&mut self, vars: &[(String, String)], record_created: bool, names: &mut HashSet<String>) {
+        self.changed = true;
+        for todo in self.todos.iter_mut() {
+            todo.instantiate(vars, record_created, names);
+        }
+    }
+
+    pub(super) fn set_all_done(&mut self, done: bool, record_completed: bool) {
+        for todo in self.todos.iter_mut() {
+            if todo.done() != done {
+                todo.set_done(done);
+                if record_completed {
+                    todo.ensure_completed();
+                }
+                self.changed = true;
+            }
+            todo.set_dependencies_done(done, record_completed);
+        }
+    }
+
+    /// Marks the list and its dependencies to be written again
+    pub(super) fn mark_unwritten(&mut self) {
+        self.changed = true;
+        for dependency in self.todos.iter_mut().filter_map(|todo| todo.dependency.as_mut()) {
+            dependency.mark_unwritten();
+        }
+    }
+
+    /// Takes the archivable todos (see Todo::is_archivable) of the list and the dependencies of
+    /// its undone todos out, with their own dependencies. The ones of dependencies are taken in
+    /// a copy of their parent (see Todo::parent_copy) that gets a file name that isn't in names.
+    /// Shared lists are left as they are. Returns them with how many todos were archivable.
+    pub(super) fn take_archivable(&mut self, before: Option<date::Type>, names: &mut HashSet<String>) -> (Vec<Todo>, usize) {
+        let (mut taken, todos): (Vec<Todo>, Vec<Todo>) = std::mem::take(&mut self.todos)
+            .into_iter()
+            .partition(|todo| todo.is_archivable(before));
+        self.todos = todos;
+        let mut count = taken.len();
+        if count > 0 {
+            self.changed = true;
+        }
+        for todo in taken.iter_mut() {
+            todo.ensure_completed();
+        }
+        for todo in self.todos.iter_mut().filter(|todo| !todo.done()) {
+            let (nested, nested_count) = match todo.dependency.as_mut().filter(|dependency| !dependency.is_shared()) {
+                Some(dependency) => dependency.todo_list_mut().take_archivable(before, names),
+                None => continue,
+            };
+            if !nested.is_empty() {
+                count += nested_count;
+                taken.push(todo.parent_copy(nested, names));
+            }
+        }
+        (taken, count)
+    }
+
+    pub(super) fn undo_done_before(&mut self, date: date::Type) {
+        for todo in self.todos.iter_mut() {
+            // Scheduled todos recur on their own
+            if todo.done() && todo.schedule.is_none() && todo.completed().is_none_or(|completed| completed < date) {
+                todo.set_done(false);
+                self.changed = true;
+            }
+            if let Some(dependency) = todo.dependency.as_mut() {
+                dependency.todo_list_mut().undo_done_before(date);
+            }
+        }
+        if self.changed {
+            self.sort();
+        }
+    }
+
+    /// Resets dependencies of the scheduled todos of the whole tree that have recurred
+    pub(super) fn reset_recurred_dependencies(&mut self, always: bool) {
+        for todo in self.todos.iter_mut() {
+            todo.reset_recurred_dependencies(always);
+            if let Some(dependency) = todo.dependency.as_mut() {
+                dependency.todo_list_mut().reset_recurred_dependencies(always);
+            }
+        }
+    }
+
+    /// Number of todos in the whole tree for each tag, tags are lowercased
+    pub fn tags(&self) -> BTreeMap<String, usize> {
+        let mut tags = BTreeMap::new();
+        self.tree_for_each(|todo| {
+            for tag in todo.tags() {
+                *tags.entry(tag.to_lowercase()).or_default() += 1;
+            }
+        });
+        tags
+    }
+
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Todo> {
         self.changed = true;
+        self.count_change();
         self.todos.iter_mut()
     }
 
@@ -279,18 +695,20 @@ impl TodoList {
 
     pub fn remove(&mut self, index: usize, restriction: &Restriction) -> Todo {
         self.changed = true;
+        self.count_change();
         let index_in_vec = self.true_position_in_list(index, restriction);
         self.todos.remove(index_in_vec)
     }
 
     pub fn push(&mut self, item: Todo) {
         self.changed = true;
+        self.count_change();
+        self.adopt(&item);
         self.todos.push(item);
     }
 
     fn compare_todos(&self, a: &Todo, b: &Todo) -> cmp::Ordering {
-        let todo_cmp = self.todo_cmp;
-        todo_cmp(a, b)
+        (self.todo_cmp)(a, b, &self.sort_attribute)
     }
 
     #[inline(always)]
@@ -313,6 +731,7 @@ impl TodoList {
 
     pub fn reorder(&mut self, index: usize) -> usize {
         self.changed = true;
+        self.count_change();
         if self
             .compare_todos(&self.todos[index], &self.todos[0])
             .is_lt()
@@ -354,16 +773,22 @@ impl TodoList {
 
     pub fn append_list(&mut self, mut todo_list: TodoList) {
         self.changed = true;
+        self.count_change();
+        for todo in &todo_list.todos {
+            self.adopt(todo);
+        }
         self.todos.append(&mut todo_list.todos);
     }
 
     pub fn sort(&mut self) {
-        self.sort_by(self.todo_cmp);
+        let (todo_cmp, sort_attribute) = (self.todo_cmp, self.sort_attribute.clone());
+        self.sort_by(|a, b| todo_cmp(a, b, &sort_attribute));
     }
 
     #[inline(always)]
     pub fn sort_by(&mut self, f: impl FnMut(&Todo, &Todo) -> cmp::Ordering) {
         self.changed = true;
+        self.count_change();
         self.todos.sort_by(f)
     }
 }
@@ -501,7 +926,7 @@ mod tests {
             .dependency
             .as_mut()
             .unwrap()
-            .todo_list
+            .todo_list_mut()
             .push(Todo::from_str("[0] Some dependency").unwrap());
         todo_list.write(&path)?;
         let dependency_path = fileio::append_notes_to_path_parent(&path);
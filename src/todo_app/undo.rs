@@ -0,0 +1,156 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use super::{ListCell, Todo, TodoList};
+use std::collections::VecDeque;
+use std::rc::Rc;
+// }}}
+
+/// Number of steps that can be undone, older ones are forgotten
+const LIMIT: usize = 100;
+/// Number of saved todos that the finished steps can keep together, older steps are forgotten
+/// when they have more, since a step keeps whole lists
+const TODOS_LIMIT: usize = 10_000;
+
+/// A list of the tree (None is the root list) with its todos before a change. The todos share
+/// the lists of their dependencies, those are saved on their own if they change.
+pub(super) struct SavedList {
+    pub(super) list: ListCell,
+    pub(super) todo_list: TodoList,
+}
+
+/// State of the app that undo and redo go back to, with the lists that a step changed
+pub(super) struct Snapshot {
+    pub(super) lists: Vec<SavedList>,
+    pub(super) tree_path: Vec<usize>,
+    pub(super) index: usize,
+    pub(super) removed_todos: Vec<Todo>,
+}
+
+impl Snapshot {
+    /// Number of todos the snapshot keeps, without the todos of their dependencies that are
+    /// shared with the tree
+    #[inline]
+    fn todos_count(&self) -> usize {
+        self.lists.iter().map(|saved| saved.todo_list.todos.len()).sum::<usize>() + self.removed_todos.len()
+    }
+
+    #[inline]
+    pub(super) fn contains(&self, list: &ListCell) -> bool {
+        self.lists.iter().any(|saved| match (&saved.list, list) {
+            (Some(saved), Some(list)) => Rc::ptr_eq(saved, list),
+            (saved, list) => saved.is_none() && list.is_none(),
+        })
+    }
+}
+
+/// Snapshots of the app before its changes. The first change after begin_step takes a
+/// snapshot and the lists that are changed until the next step are added to it, so everything
+/// that is done in a step is undone at once.
+#[derive(Default)]
+pub(super) struct UndoHistory {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    step_started: bool,
+    recording: bool,
+}
+
+impl UndoHistory {
+    #[inline]
+    pub(super) fn begin_step(&mut self) {
+        self.step_started = true;
+    }
+
+    #[inline]
+    pub(super) fn is_step_started(&self) -> bool {
+        self.step_started
+    }
+
+    /// Whether a snapshot should be pushed before a change
+    #[inline]
+    pub(super) fn take_step(&mut self) -> bool {
+        std::mem::take(&mut self.step_started)
+    }
+
+    /// The snapshot of the step that changes are made in, if there's one
+    #[inline]
+    pub(super) fn recording(&mut self) -> Option<&mut Snapshot> {
+        self.undo.back_mut().filter(|_| self.recording)
+    }
+
+    #[inline]
+    pub(super) fn push(&mut self, snapshot: Snapshot) {
+        let mut todos_count: usize = self.undo.iter().map(Snapshot::todos_count).sum();
+        while self.undo.len() >= LIMIT || todos_count > TODOS_LIMIT {
+            if let Some(forgotten) = self.undo.pop_front() {
+                todos_count -= forgotten.todos_count();
+            }
+        }
+        self.undo.push_back(snapshot);
+        self.redo.clear();
+        self.recording = true;
+    }
+
+    /// Forgets the last snapshot, for steps that turned out not to change anything
+    #[inline]
+    pub(super) fn pop(&mut self) {
+        self.undo.pop_back();
+        self.recording = false;
+    }
+
+    /// Takes the last snapshot out to go back to it, current (see App::snapshot_of) is
+    /// what redo goes back to
+    #[inline]
+    pub(super) fn undo(&mut self, current: impl FnOnce(&Snapshot) -> Snapshot) -> Option<Snapshot> {
+        let snapshot = self.undo.pop_back()?;
+        self.redo.push(current(&snapshot));
+        self.recording = false;
+        Some(snapshot)
+    }
+
+    #[inline]
+    pub(super) fn redo(&mut self, current: impl FnOnce(&Snapshot) -> Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo.pop()?;
+        self.undo.push_back(current(&snapshot));
+        self.recording = false;
+        Some(snapshot)
+    }
+
+    #[inline]
+    pub(super) fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.recording = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(todos: usize) -> Snapshot {
+        let mut todo_list = TodoList::default();
+        for _ in 0..todos {
+            todo_list.push(Todo::default());
+        }
+        Snapshot {
+            lists: vec![SavedList { list: None, todo_list }],
+            tree_path: vec![],
+            index: 0,
+            removed_todos: vec![],
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut history = UndoHistory::default();
+        for _ in 0..LIMIT + 10 {
+            history.push(snapshot(1));
+        }
+        assert_eq!(history.undo.len(), LIMIT);
+        for _ in 0..3 {
+            history.push(snapshot(TODOS_LIMIT / 2 + 1));
+        }
+        assert_eq!(history.undo.len(), 2);
+        assert_eq!(history.undo.iter().map(Snapshot::todos_count).sum::<usize>(), TODOS_LIMIT + 2);
+    }
+}
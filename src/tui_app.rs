@@ -24,17 +24,22 @@ use std::{
     rc::Rc,
 };
 use tui_textarea::{CursorMove, Input, TextArea};
+pub(crate) mod keymap;
+mod breadcrumbs;
+mod clipboard;
 mod potato;
-mod todo_buffer;
-use todo_buffer::TodoBuffer;
+mod theme;
 mod tree_search;
 pub use tree_search::TreeSearch;
 use c3::{
-    date,
-    todo_app::{App, Restriction, Schedule, Todo},
+    config::Config,
+    date, fileio, TodoDisplay,
+    todo_app::{register, template, text::{self, TextFormat}, App, Attributes, OutlineRow, Restriction, Schedule, Todo, TodoList},
 };
 
 use potato::Potato;
+use keymap::{Action, KeyChord, Keymap, Lookup};
+use theme::Theme;
 // }}}
 
 #[derive(Debug)]
@@ -60,7 +65,12 @@ enum Mode {
 
 pub struct TuiApp<'a> {
     tree_search: TreeSearch,
-    todo_buffer: TodoBuffer,
+    /// Register of the next yank, cut or paste
+    register: Option<String>,
+    awaiting_register: bool,
+    /// The next key is the number of a breadcrumb to go to
+    awaiting_level: bool,
+    registers_popup: Option<Vec<String>>,
     last_restriction: Option<Restriction>,
     show_right: bool,
     mode: Mode,
@@ -71,6 +81,13 @@ pub struct TuiApp<'a> {
     potato_module: Potato,
     textarea: TextArea<'a>,
     todo_app: &'a mut App,
+    keymap: Keymap,
+    theme: Theme,
+    pending_keys: Vec<KeyChord>,
+    /// First row of the outline that is shown
+    outline_offset: usize,
+    /// Error of the last action, shown in the title until the next key
+    error: Option<String>,
 }
 
 #[derive(Parser, Debug)]
@@ -87,16 +104,43 @@ pub struct TuiArgs {
     /// Enable TUI module at startup
     #[arg(short = 'm', long)]
     enable_module: bool,
+
+    /// Show the whole tree in the list (outline) at startup
+    #[arg(long)]
+    outline: bool,
+
+    /// Keymap file, $XDG_CONFIG_HOME/c3/keymap.toml by default
+    #[arg(long)]
+    keymap: Option<PathBuf>,
+
+    /// Theme of the TUI, default, plain or one of the [themes] of the config file
+    #[arg(long, default_value = "default")]
+    theme: String,
+
+    /// Format of the todos copied to the system clipboard
+    #[arg(long, default_value = "message")]
+    clipboard_format: TextFormat,
+
+    /// Command that gets the copied text as its input (like wl-copy), OSC 52 is used by default
+    #[arg(long)]
+    copy_command: Option<String>,
+
+    /// Command that outputs the clipboard, wl-paste, xclip or pbpaste by default
+    #[arg(long)]
+    paste_command: Option<String>,
 }
 
 impl<'a> TuiApp<'a> {
     #[inline]
-    pub fn new(app: &'a mut App, args: TuiArgs) -> Self {
+    pub fn new(app: &'a mut App, args: TuiArgs, keymap: Keymap, theme: Theme) -> Self {
         let mut textarea = TextArea::default();
         textarea.set_cursor_line_style(Style::default());
         TuiApp {
             tree_search: Default::default(),
-            todo_buffer: Default::default(),
+            register: None,
+            awaiting_register: false,
+            awaiting_level: false,
+            registers_popup: None,
             todo_app: app,
             args,
             textarea,
@@ -105,6 +149,11 @@ impl<'a> TuiApp<'a> {
             on_input: None,
             on_delete: None,
             show_right: true,
+            keymap,
+            theme,
+            pending_keys: vec![],
+            outline_offset: 0,
+            error: None,
             mode: Default::default(),
             last_restriction: None,
         }
@@ -112,7 +161,7 @@ impl<'a> TuiApp<'a> {
 
     #[inline]
     pub fn title(&mut self) -> String {
-        let changed_str = if self.todo_app.current_list().changed {
+        let changed_str = if self.todo_app.current_list().changed && !self.todo_app.is_read_only() {
             "*"
         } else {
             ""
@@ -121,13 +170,20 @@ impl<'a> TuiApp<'a> {
             .todo_app
             .current_list()
             .len(self.todo_app.get_restriction());
-        let todo_string = format!("Todos ({size}){changed_str}");
+        let name = if self.todo_app.is_read_only() { "Archive" } else { "Todos" };
+        let mut todo_string = format!("{name} ({size}){changed_str}");
+        let selected = self.todo_app.selected_indices().len();
+        if selected > 0 || self.todo_app.is_visual() {
+            todo_string.push_str(&format!(" [{selected} selected]"));
+        }
 
         if let Some(parent) = self.todo_app.parent() {
-            format!("{todo_string} {}", parent.message)
-        } else {
-            todo_string
+            todo_string = format!("{todo_string} {}", parent.message);
+        }
+        if let Some(error) = &self.error {
+            todo_string = format!("{todo_string} | {error}");
         }
+        todo_string
     }
 
     #[inline]
@@ -222,9 +278,9 @@ impl<'a> TuiApp<'a> {
 
     #[inline]
     fn on_tree_search(&mut self, query: String) {
-        let current_not_matches = self.todo_app.todo().map_or(true, |todo| !todo.matches(&query));
+        let current_not_matches = self.todo_app.todo().map_or(true, |todo| !TreeSearch::matches(&todo, &query));
 
-        self.tree_search.search(query, self.todo_app.current_list(), Rc::clone(self.todo_app.get_restriction()));
+        self.tree_search.search(query, &self.todo_app.current_list(), Rc::clone(self.todo_app.get_restriction()));
         if current_not_matches {
             self.tree_search.next();
             self.tree_search.set_to_app(self.todo_app);
@@ -242,14 +298,31 @@ impl<'a> TuiApp<'a> {
         if day.is_none() {
             return;
         }
-        if let Some(todo) = self.todo_app.todo_mut() {
-            todo.enable_day(day.unwrap() as i64);
-        }
+        let day = day.unwrap() as i64;
+        self.todo_app.edit_selected(|todo| todo.enable_day(day));
     }
 
     #[inline]
     pub fn reminder_prompt(&mut self) {
-        self.set_text_mode(Self::on_reminder, "Date reminder", "");
+        self.set_text_mode(Self::on_reminder, "Date reminder", "today, +3d, fri 14:30, dec 24, 2026-12-24");
+        self.on_input = Some(Self::on_date_input);
+        self.on_delete = Some(Self::on_date_delete);
+    }
+
+    #[inline]
+    fn on_date_input(&mut self, str: String) {
+        let (format, calendar) = (self.todo_app.args.display_args.date_format(), self.todo_app.args.display_args.calendar());
+        let title = match date::parse_user_input_with_time(&str, calendar) {
+            Ok((date, Some(time))) => format!("Date reminder ({} {})", date::display(Some(date), format, calendar), date::format_time(time)),
+            Ok((date, None)) => format!("Date reminder ({})", date::display(Some(date), format, calendar)),
+            Err(_) => String::from("Date reminder"),
+        };
+        self.textarea.set_block(default_block(title));
+    }
+
+    #[inline]
+    fn on_date_delete(&mut self, str: String, _: String) {
+        self.on_date_input(str)
     }
 
     fn nnn_paths() -> Option<impl Iterator<Item = PathBuf>> {
@@ -297,11 +370,12 @@ impl<'a> TuiApp<'a> {
 
     #[inline]
     fn on_reminder(&mut self, str: String) {
-        if let Ok(date) = date::parse_user_input(&str) {
-            if let Some(todo) = self.todo_app.todo_mut() {
-                todo.schedule = Some(Schedule::new_reminder(date));
-                self.todo_app.reorder_current();
-            }
+        if let Ok((date, time)) = date::parse_user_input_with_time(&str, self.todo_app.args.display_args.calendar()) {
+            self.todo_app.edit_selected(|todo| {
+                let mut schedule = Schedule::new_reminder(date);
+                schedule.set_time(time);
+                todo.schedule = Some(schedule);
+            });
         }
     }
 
@@ -316,6 +390,111 @@ impl<'a> TuiApp<'a> {
         }
     }
 
+    #[inline]
+    pub fn attributes_prompt(&mut self) {
+        if let Some(attributes) = self.todo_app.todo().map(|todo| todo.attributes().to_string()) {
+            self.set_text_mode(Self::on_attributes, "Edit attributes", "key:value key:value");
+            self.textarea.insert_str(attributes);
+        }
+    }
+
+    #[inline]
+    pub fn block_prompt(&mut self) {
+        self.set_text_mode(
+            Self::on_block,
+            "Blocked by",
+            "Enter search query of the blocking todo (whole tree)",
+        )
+    }
+
+    #[inline]
+    fn on_block(&mut self, query: String) {
+        if !query.is_empty() {
+            self.todo_app.block_current_by(&query);
+        }
+    }
+
+    #[inline]
+    pub fn move_into_prompt(&mut self) {
+        self.set_text_mode(
+            Self::on_move_into,
+            "Move into",
+            "Enter search query of the todo to move into (current list)",
+        )
+    }
+
+    #[inline]
+    fn on_move_into(&mut self, query: String) {
+        if !query.is_empty() {
+            self.todo_app.move_into_match(&query);
+        }
+    }
+
+    #[inline]
+    pub fn move_to_prompt(&mut self) {
+        self.set_text_mode(
+            Self::on_move_to,
+            "Move to",
+            "Enter search query of the todo to move into (whole tree)",
+        )
+    }
+
+    #[inline]
+    fn on_move_to(&mut self, query: String) {
+        if !query.is_empty() {
+            self.todo_app.move_to_match(&query);
+        }
+    }
+
+    #[inline]
+    pub fn move_to_file_prompt(&mut self) {
+        self.set_text_mode(Self::on_move_to_file, "Move to file", "Enter the path of the todo file");
+    }
+
+    #[inline]
+    fn on_move_to_file(&mut self, path: String) {
+        let path = path.trim();
+        if !path.is_empty() {
+            if let Err(error) = self.todo_app.move_to_file(&fileio::expand_home(path)) {
+                self.error = Some(format!("Couldn't move to {path}: {error}"));
+            }
+        }
+    }
+
+    #[inline]
+    pub fn save_template_prompt(&mut self) {
+        if self.todo_app.todo().is_some() {
+            self.set_text_mode(Self::on_save_template, "Save as template", "Enter the template name");
+        }
+    }
+
+    #[inline]
+    fn on_save_template(&mut self, name: String) {
+        let name = name.trim();
+        if template::is_valid_name(name) {
+            let _ = self.todo_app.save_current_as_template(name);
+        }
+    }
+
+    #[inline]
+    pub fn template_prompt(&mut self) {
+        let names = template::names(&self.todo_app.args.todo_path);
+        let placeholder = match names.is_empty() {
+            true => String::from("No templates yet, save one with Y"),
+            false => names.join(" "),
+        };
+        self.set_text_mode(Self::on_template, "Add from template (name key=value...)", &placeholder);
+    }
+
+    #[inline]
+    fn on_template(&mut self, input: String) {
+        let mut words = input.split_whitespace();
+        if let Some(name) = words.next() {
+            let vars = template::parse_vars(words);
+            self.todo_app.append_template(name, None, &vars);
+        }
+    }
+
     #[inline]
     pub fn prepend_prompt(&mut self) {
         self.set_text_mode(Self::on_append_todo, "Add todo", "Enter the todo message");
@@ -341,6 +520,20 @@ impl<'a> TuiApp<'a> {
         self.on_delete = Some(Self::on_priority_delete);
     }
 
+    #[inline]
+    pub fn tag_restriction_prompt(&mut self) {
+        const TITLE: &str = "Limit tag";
+        let tags = self.todo_app.todo_list.tags();
+        let placeholder = match tags.is_empty() {
+            true => String::from("Enter tag or context to show"),
+            false => tags.into_keys().collect::<Vec<_>>().join(" "),
+        };
+        self.last_restriction = Some(self.todo_app.get_restriction().clone());
+        self.set_text_mode(Self::on_tag_prompt, TITLE, &placeholder);
+        self.set_responsive_text_mode(Self::on_tag_prompt, TITLE, &placeholder);
+        self.on_delete = Some(Self::on_priority_delete);
+    }
+
     #[inline]
     pub fn append_prompt(&mut self) {
         self.set_text_mode(
@@ -352,7 +545,7 @@ impl<'a> TuiApp<'a> {
 
     #[inline]
     pub fn quit_save_prompt(&mut self) {
-        if self.todo_app.is_changed() || self.todo_app.current_list().changed {
+        if self.todo_app.is_changed() || (self.todo_app.current_list().changed && !self.todo_app.is_read_only()) {
             self.set_text_mode(
                 Self::on_save_prompt,
                 "You have done changes. You wanna save? [n: no, y: yes, c: cancel] (default: n)",
@@ -375,6 +568,18 @@ impl<'a> TuiApp<'a> {
         }
     }
 
+    #[inline]
+    fn on_tag_prompt(&mut self, str: String) {
+        if str.is_empty() {
+            return self.todo_app.update_show_done_restriction();
+        }
+        let tags: Vec<String> = str.split_whitespace().map(String::from).collect();
+        self.todo_app.set_restriction_with_last(
+            Rc::new(move |todo| tags.iter().all(|tag| todo.has_tag(tag))),
+            self.last_restriction.clone(),
+        )
+    }
+
     #[inline]
     fn on_schedule_prompt(&mut self, str: String) {
         if str.is_empty() {
@@ -411,11 +616,19 @@ impl<'a> TuiApp<'a> {
     }
 
     #[inline]
-    fn on_edit_todo(&mut self, str: String) {
-        if !str.is_empty() {
-            if let Some(todo) = self.todo_app.todo_mut() {
-                todo.message = str;
+    fn on_attributes(&mut self, str: String) {
+        if let Ok(attributes) = str.parse::<Attributes>() {
+            if let Some(mut todo) = self.todo_app.todo_mut() {
+                todo.set_attributes(attributes);
             }
+            self.todo_app.reorder_current();
+        }
+    }
+
+    #[inline]
+    fn on_edit_todo(&mut self, str: String) {
+        if !str.is_empty() && self.todo_app.todo_mut().map(|mut todo| todo.set_message(str)).is_some() {
+            self.todo_app.reorder_current();
         }
     }
 
@@ -507,125 +720,305 @@ impl<'a> TuiApp<'a> {
         let event = event::read()?;
         if let Key(key) = event {
             if key.kind == event::KeyEventKind::Press {
-                match key.code {
-                    #[cfg(unix)]
-                    Char('z') if key.modifiers == KeyModifiers::CONTROL => {
-                        shutdown()?;
-                        let _ = kill(getpid(), Signal::SIGTSTP);
-                        return Ok(HandlerOperation::Restart);
-                    }
-                    Char('o') if key.modifiers == KeyModifiers::CONTROL => {
-                        self.nnn_open();
-                        return Ok(HandlerOperation::Restart);
-                    }
-                    Char('x') => {
-                        self.todo_app.remove_todo();
-                        if let Some(todo) = self.todo_app.removed_todos.pop() {
-                            self.todo_buffer.yank(todo);
-                        }
-                    },
-                    Char('d') => self.todo_app.toggle_current_daily(),
-                    Char('W') => self.todo_app.toggle_current_weekly(),
-                    Char('S') => self.schedule_prompt(),
-                    Char('m') => self.reminder_prompt(),
-                    Char('M') => {
-                        if let Some(todo) = self.todo_app.todo_mut() {
-                            todo.toggle_schedule();
+                self.error = None;
+                if self.registers_popup.take().is_some() {
+                    return Ok(HandlerOperation::Nothing);
+                }
+                if std::mem::take(&mut self.awaiting_register) {
+                    self.register = match key.code {
+                        KeyCode::Char('"') => Some(String::from(register::DEFAULT)),
+                        KeyCode::Char(c) if c.is_ascii_alphanumeric() => Some(c.to_string()),
+                        _ => None,
+                    };
+                    return Ok(HandlerOperation::Nothing);
+                }
+                if std::mem::take(&mut self.awaiting_level) {
+                    if let Char(c) = key.code {
+                        if let Some(level) = c.to_digit(10) {
+                            self.todo_app.go_to_depth(level as usize);
                         }
                     }
-                    Char('!') => self.todo_app.toggle_show_done(),
-                    Char('@') => self.priority_prompt(),
-                    Char('%') => self.schedule_restriction_prompt(),
-                    Char('y') => {
-                        let todo = self.todo_app.todo().cloned();
-                        self.todo_buffer.yank(todo);
-                    }
-                    Char('p') => {
-                        if let Some(todo) = self.todo_buffer.get() {
-                            let list = self.todo_app.current_list_mut();
-                            list.push(todo);
-                            self.todo_app.index = list.reorder_last();
+                    return Ok(HandlerOperation::Nothing);
+                }
+                self.pending_keys.push(KeyChord::from(key));
+                match self.keymap.lookup(&self.pending_keys) {
+                    Lookup::Prefix => {}
+                    Lookup::Action(action) => {
+                        self.pending_keys.clear();
+                        self.todo_app.begin_undo_step();
+                        let operation = self.run_action(action);
+                        if action != Action::Register {
+                            self.register = None;
                         }
+                        return operation;
                     }
-                    Char('i') => self.todo_app.increase_day_by(1),
-                    Char('I') => self.todo_app.increase_day_by(-1),
-                    Char('o') => {
-                        self.nnn_append_todo();
-                        return Ok(HandlerOperation::Restart);
-                    }
-                    Char('O') => {
-                        self.nnn_output_todo();
-                        return Ok(HandlerOperation::Restart);
-                    }
-                    KeyCode::Down | Char('j') => self.todo_app.go_down(),
-                    KeyCode::Up | Char('k') => self.todo_app.go_up(),
-                    KeyCode::Right | Char('l') => self.todo_app.add_dependency_traverse_down(),
-                    KeyCode::Enter => self.todo_app.traverse_down(),
-                    KeyCode::Left | Char('h') => {
-                        self.todo_app.traverse_up();
-                    }
-                    KeyCode::Home | Char('g') => {
-                        self.todo_app.index = 0;
-                    }
-                    KeyCode::End | Char('G') => self.todo_app.index = self.todo_app.bottom(),
-                    Char('w') => self.write()?,
-                    Char('J') => self.todo_app.move_current_down(),
-                    Char('K') => self.todo_app.move_current_up(),
-                    Char(']') => self.show_right = !self.show_right,
-                    Char('P') => self.args.enable_module = !self.args.enable_module,
-                    Char('>') => {
-                        self.todo_app.edit_or_add_note();
-                        return Ok(HandlerOperation::Restart);
-                    }
-                    Char('t') => self.todo_app.add_dependency(),
-                    Char('D') => {
-                        self.todo_app.remove_todo();
-                    }
-                    Char('R') => self.todo_app.read(),
-                    Char('T') => self.todo_app.remove_current_dependent(),
-                    Char(' ') => self.todo_app.toggle_current_done(),
-                    Char('n') => {
-                        self.tree_search.next();
-                        self.tree_search.set_to_app(self.todo_app);
-                    }
-                    Char('a') => self.prepend_prompt(),
-                    Char('/') => self.search_prompt(),
-                    Char('?') => self.tree_search_prompt(),
-                    Char('A') => self.append_prompt(),
-                    Char('e') | Char('E') => self.edit_prompt(key.code == Char('E')),
-                    Char('r') if key.modifiers == KeyModifiers::CONTROL => self.edit_prompt(false),
-                    Char('~') => self.todo_app.go_root(),
-                    Char('q') => self.quit_save_prompt(),
-                    Char('r') => {
-                        self.todo_app.batch_editor_messages();
-                        return Ok(HandlerOperation::Restart);
+                    Lookup::None => self.pending_keys.clear(),
+                }
+            }
+        }
+        Ok(HandlerOperation::Nothing)
+    }
+
+    fn run_action(&mut self, action: Action) -> io::Result<HandlerOperation> {
+        if action.is_module() && !self.args.enable_module || action.is_mutating() && self.todo_app.is_read_only() {
+            return Ok(HandlerOperation::Nothing);
+        }
+        match action {
+            Action::Suspend => {
+                #[cfg(unix)]
+                {
+                    shutdown()?;
+                    let _ = kill(getpid(), Signal::SIGTSTP);
+                    return Ok(HandlerOperation::Restart);
+                }
+            }
+            Action::OpenFile => {
+                self.nnn_open();
+                return Ok(HandlerOperation::Restart);
+            }
+            Action::Cut => {
+                // Removed todos have their files deleted on write, the register has its own copy
+                if self.yank_to_register() {
+                    self.todo_app.remove_todo();
+                    self.todo_app.update_shared_dependencies();
+                }
+            },
+            Action::Register => self.awaiting_register = true,
+            Action::Registers => self.registers_popup = Some(self.registers_lines()),
+            Action::MoveToParent => {
+                self.todo_app.move_to_parent();
+            }
+            Action::MoveInto => self.move_into_prompt(),
+            Action::MoveTo => self.move_to_prompt(),
+            Action::MoveToFile => self.move_to_file_prompt(),
+            Action::Visual => self.todo_app.toggle_visual(),
+            Action::ToggleMark => self.todo_app.toggle_mark(),
+            Action::ClearSelection => self.todo_app.clear_selection(),
+            Action::ToggleDaily => self.todo_app.toggle_current_daily(),
+            Action::ToggleWeekly => self.todo_app.toggle_current_weekly(),
+            Action::ToggleResetsList => self.todo_app.toggle_current_resets_dependencies(),
+            Action::SetSchedule => self.schedule_prompt(),
+            Action::SetReminder => self.reminder_prompt(),
+            Action::ToggleSchedule => self.todo_app.edit_selected(|todo| {
+                todo.toggle_schedule();
+            }),
+            Action::ToggleShowDone => self.todo_app.toggle_show_done(),
+            Action::RestrictPriority => self.priority_prompt(),
+            Action::RestrictSchedule => self.schedule_restriction_prompt(),
+            Action::RestrictTag => self.tag_restriction_prompt(),
+            Action::EditAttributes => self.attributes_prompt(),
+            Action::Block => self.block_prompt(),
+            Action::ToggleHideBlocked => self.todo_app.toggle_hide_blocked(),
+            Action::Archive => {
+                if let Err(error) = self.todo_app.archive_done(None) {
+                    self.error = Some(format!("Couldn't archive: {error}"));
+                }
+            }
+            Action::SaveTemplate => self.save_template_prompt(),
+            Action::AddFromTemplate => self.template_prompt(),
+            Action::Yank => {
+                self.yank_to_register();
+                self.todo_app.clear_selection();
+            }
+            Action::Paste => {
+                let name = self.take_register();
+                self.todo_app.paste_register(&register::dir(), &name);
+            }
+            Action::PasteAsLink => {
+                let name = self.take_register();
+                self.todo_app.paste_register_as_link(&register::dir(), &name);
+            }
+            Action::CopyToClipboard => {
+                let text = text::render(&self.todo_app.selected_todos(), self.args.clipboard_format);
+                if !text.is_empty() {
+                    if let Err(error) = clipboard::copy(&text, self.args.copy_command.as_deref()) {
+                        self.error = Some(format!("Couldn't copy: {error}"));
                     }
-                    Char(c) if c.is_ascii_digit() => {
-                        let priority = c.to_digit(10).unwrap();
-                        self.todo_app.set_current_priority(priority as u8);
+                }
+                self.todo_app.clear_selection();
+            }
+            Action::PasteFromClipboard => {
+                match clipboard::paste(self.args.paste_command.as_deref()) {
+                    Ok(text) => {
+                        self.todo_app.paste_text(&text);
                     }
+                    Err(error) => self.error = Some(format!("Couldn't paste: {error}")),
+                }
+            }
+            Action::IncreaseDay => self.todo_app.increase_day_by(1),
+            Action::DecreaseDay => self.todo_app.increase_day_by(-1),
+            Action::AppendFile => {
+                self.nnn_append_todo();
+                return Ok(HandlerOperation::Restart);
+            }
+            Action::OutputFile => {
+                self.nnn_output_todo();
+                return Ok(HandlerOperation::Restart);
+            }
+            Action::GoDown if self.args.outline => self.outline_go(|index, len| (index + 1) % len),
+            Action::GoUp if self.args.outline => self.outline_go(|index, len| (index + len - 1) % len),
+            Action::GoTop if self.args.outline => self.outline_go(|_, _| 0),
+            Action::GoBottom if self.args.outline => self.outline_go(|_, len| len - 1),
+            Action::GoDown => self.todo_app.go_down(),
+            Action::GoUp => self.todo_app.go_up(),
+            Action::GoInOrAddDependency => self.todo_app.add_dependency_traverse_down(),
+            Action::GoIn => self.todo_app.traverse_down(),
+            Action::GoBack => {
+                self.todo_app.traverse_up();
+            }
+            Action::GoTop => {
+                self.todo_app.index = 0;
+            }
+            Action::GoBottom => self.todo_app.index = self.todo_app.bottom(),
+            Action::Write => self.write()?,
+            Action::MoveDown => self.todo_app.move_current_down(),
+            Action::MoveUp => self.todo_app.move_current_up(),
+            Action::ToggleDependencyView => self.show_right = !self.show_right,
+            Action::ToggleModule => self.args.enable_module = !self.args.enable_module,
+            Action::ToggleOutline => self.args.outline = !self.args.outline,
+            Action::OpenFold => self.todo_app.open_fold(),
+            Action::CloseFold => self.todo_app.close_fold(),
+            Action::ToggleFold => self.todo_app.toggle_fold(),
+            Action::OpenAllFolds => self.todo_app.open_all_folds(),
+            Action::CloseAllFolds => self.todo_app.close_all_folds(),
+            Action::EditNote => {
+                self.todo_app.edit_or_add_note();
+                return Ok(HandlerOperation::Restart);
+            }
+            Action::AddDependency => self.todo_app.add_dependency(),
+            Action::Delete => {
+                self.todo_app.remove_todo();
+            }
+            Action::Read => self.todo_app.read(),
+            Action::RemoveDependency => self.todo_app.remove_current_dependent(),
+            Action::ToggleDone => self.todo_app.toggle_current_done(),
+            Action::SearchNext => {
+                self.tree_search.next();
+                self.tree_search.set_to_app(self.todo_app);
+            }
+            Action::Add => self.prepend_prompt(),
+            Action::Search => self.search_prompt(),
+            Action::TreeSearch => self.tree_search_prompt(),
+            Action::AddFirst => self.append_prompt(),
+            Action::Edit => self.edit_prompt(false),
+            Action::EditAtStart => self.edit_prompt(true),
+            Action::Redo => {
+                self.todo_app.redo();
+            }
+            Action::Undo => {
+                self.todo_app.undo();
+            }
+            Action::GoRoot => self.todo_app.go_root(),
+            Action::GoToLevel => self.awaiting_level = true,
+            Action::Quit => self.quit_save_prompt(),
+            Action::BatchEdit => {
+                self.todo_app.batch_editor_messages();
+                return Ok(HandlerOperation::Restart);
+            }
 
-                    Char('s') => self.potato_module.skip(),
-                    Char('H') => self.potato_module.increase_timer(),
-                    Char('c') => self.potato_module.toggle_pause(),
-                    Char('C') => self.potato_module.quit(),
-                    Char('L') => self.potato_module.decrease_timer(),
-                    Char('f') => self.potato_module.restart(),
-                    Char('+') | Char('=') => self.potato_module.increase_pomodoro(),
-                    Char('-') => self.potato_module.decrease_pomodoro(),
-                    Char('.') => self.potato_module.next(),
-                    Char(',') => self.potato_module.prev(),
-                    _ => {}
+            Action::PotatoSkip => self.potato_module.skip(),
+            Action::PotatoIncreaseTimer => self.potato_module.increase_timer(),
+            Action::PotatoTogglePause => self.potato_module.toggle_pause(),
+            Action::PotatoQuit => self.potato_module.quit(),
+            Action::PotatoDecreaseTimer => self.potato_module.decrease_timer(),
+            Action::PotatoRestart => self.potato_module.restart(),
+            Action::PotatoIncreasePomodoro => self.potato_module.increase_pomodoro(),
+            Action::PotatoDecreasePomodoro => self.potato_module.decrease_pomodoro(),
+            Action::PotatoNextServer => self.potato_module.next(),
+            Action::PotatoPrevServer => self.potato_module.prev(),
+            Action::Priority0
+            | Action::Priority1
+            | Action::Priority2
+            | Action::Priority3
+            | Action::Priority4
+            | Action::Priority5
+            | Action::Priority6
+            | Action::Priority7
+            | Action::Priority8
+            | Action::Priority9 => {
+                if let Some(priority) = action.priority() {
+                    self.todo_app.set_current_priority(priority);
                 }
             }
         }
         Ok(HandlerOperation::Nothing)
     }
 
+    #[inline]
+    fn take_register(&mut self) -> String {
+        self.register.take().unwrap_or_else(|| String::from(register::DEFAULT))
+    }
+
+    /// Writes the selected todos (or the current one) to the register, returns whether it did
+    fn yank_to_register(&mut self) -> bool {
+        let name = self.take_register();
+        let todos = self.todo_app.selected_todos();
+        !todos.is_empty() && self.todo_app.write_register(&register::dir(), &name, &todos).is_ok()
+    }
+
+    /// A line for each register, with the message of its first todo
+    fn registers_lines(&self) -> Vec<String> {
+        let dir = register::dir();
+        register::names(&dir)
+            .into_iter()
+            .map(|name| {
+                let register = self.todo_app.read_register(&dir, &name);
+                let first = register.todos.first().map(|todo| todo.message.as_str()).unwrap_or_default();
+                let name = if name == register::DEFAULT { "\"" } else { &name };
+                match register.todos.len() {
+                    0 | 1 => format!("{name}  {first}"),
+                    len => format!("{name}  {first} (+{})", len - 1),
+                }
+            })
+            .collect()
+    }
+
+    /// Numbered breadcrumbs of the root list and the parents of the current list
+    fn render_breadcrumbs(&self, frame: &mut Frame, layout: Rect) {
+        let name = if self.todo_app.is_read_only() { "Archive" } else { "Todos" };
+        let parents = self.todo_app.parents();
+        let crumbs: Vec<&str> = [name]
+            .into_iter()
+            .chain(parents.iter().map(|todo| todo.message.as_str()))
+            .collect();
+        let line = breadcrumbs::line(&crumbs, layout.width as usize);
+        frame.render_widget(Paragraph::new(line).style(Style::default().add_modifier(Modifier::DIM)), layout);
+    }
+
+    fn render_registers_popup(frame: &mut Frame, lines: &[String]) {
+        let area = frame.size();
+        let width = area.width.saturating_sub(4).min(60);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+        let items: Vec<ListItem> = lines.iter().map(|line| ListItem::new(line.as_str())).collect();
+        frame.render_widget(Clear, popup);
+        frame.render_widget(List::new(items).block(default_block("Registers")), popup);
+    }
+
+    /// Goes to the row of the outline that f returns, from the index of the current row and the
+    /// number of rows
+    fn outline_go(&mut self, f: impl FnOnce(usize, usize) -> usize) {
+        let rows = self.todo_app.outline();
+        if rows.is_empty() {
+            return;
+        }
+        let current = self.current_row(&rows).unwrap_or_default();
+        self.todo_app.go_to_position(&rows[f(current, rows.len())].position);
+    }
+
+    #[inline]
+    fn current_row(&self, rows: &[OutlineRow]) -> Option<usize> {
+        let position = self.todo_app.position()?;
+        rows.iter().position(|row| row.position == position)
+    }
+
+    /// Todo lists are shown in the outline, only notes are shown on the right
     #[inline]
     fn is_dependency_enabled(&self, todo: Option<&Todo>) -> bool {
         todo.map_or(false, |todo| {
-            self.show_right && todo.dependency.is_some() && self.todo_app.is_tree()
+            self.show_right
+                && todo.dependency.as_ref().is_some_and(|dep| !self.args.outline || dep.note().is_some())
+                && self.todo_app.is_tree()
         })
     }
 
@@ -670,7 +1063,7 @@ impl<'a> TuiApp<'a> {
                     frame,
                     None,
                     dependency_layout,
-                    self.todo_app.display_a_slice(todo_list, 0, dependency_layout.height as usize - 2),
+                    self.todo_items(&todo_list, 0, dependency_layout.height as usize - 2),
                     String::from("Todo dependencies"),
                 )
             }
@@ -685,16 +1078,29 @@ impl<'a> TuiApp<'a> {
         todo_layout: Rect,
     ) {
         let title = self.title();
-        let display = if self.args.minimal_render {
+        let display = if self.args.outline {
+            // Only the rows that fit are made into items, the list is scrolled here instead
+            let rows = self.todo_app.outline();
+            let height = (todo_layout.height as usize).saturating_sub(2).max(1);
+            let current = self.current_row(&rows);
+            self.outline_offset = match current {
+                Some(current) if self.args.minimal_render || current < self.outline_offset => current,
+                Some(current) if current >= self.outline_offset + height => current + 1 - height,
+                _ => self.outline_offset.min(rows.len().saturating_sub(1)),
+            };
+            list_state.select(current.map(|current| current - self.outline_offset));
+            *list_state.offset_mut() = 0;
+            self.outline_items(&rows, self.outline_offset, self.outline_offset + height)
+        } else if self.args.minimal_render {
             let first = self.todo_app.index();
             let last = self
                 .todo_app
                 .current_list()
                 .len(self.todo_app.get_restriction())
                 .min(todo_layout.height as usize + first - 2);
-            self.todo_app.display_a_slice(self.todo_app.current_list(), first, last)
+            self.todo_items(&self.todo_app.current_list(), first, last)
         } else {
-            self.todo_app.display_current_list()
+            self.todo_items(&self.todo_app.current_list(), 0, usize::MAX)
         };
         Self::render_todos_widget(
             self.highlight_string(),
@@ -706,13 +1112,52 @@ impl<'a> TuiApp<'a> {
         )
     }
 
+    /// Styled todos of the list, like App::display_a_slice
+    #[inline]
+    fn todo_items(&self, todo_list: &TodoList, min: usize, max: usize) -> Vec<ListItem<'static>> {
+        let args = &self.todo_app.args.display_args;
+        let is_current = std::ptr::eq(todo_list, &*self.todo_app.current_list());
+        todo_list
+            .todos(self.todo_app.get_restriction())
+            .enumerate()
+            .skip(min)
+            .take(max)
+            .map(|(index, todo)| {
+                let selected = is_current && self.todo_app.is_selected(index);
+                self.theme.item(todo, todo.display_with_args(args), selected)
+            })
+            .collect()
+    }
+
+    /// Styled todos of the outline rows from min to max, after the tree lines of their rows
+    fn outline_items(&self, rows: &[OutlineRow], min: usize, max: usize) -> Vec<ListItem<'static>> {
+        let args = &self.todo_app.args.display_args;
+        let is_current = |row: &OutlineRow| row.position[..row.depth()] == *self.todo_app.tree_path();
+        let min = min.min(rows.len());
+        let mut current_list_index = rows[..min].iter().filter(|row| is_current(row)).count();
+        rows[min..max.min(rows.len())]
+            .iter()
+            .filter_map(|row| {
+                let todo = self.todo_app.todo_at(&row.position)?;
+                let mut selected = false;
+                if is_current(row) {
+                    selected = self.todo_app.is_selected(current_list_index);
+                    current_list_index += 1;
+                }
+                let fold = if row.folded { " …" } else { "" };
+                let display = format!("{}{fold}", todo.display_with_args(args));
+                Some(self.theme.tree_item(&todo, row.prefix(), display, selected))
+            })
+            .collect()
+    }
+
     #[inline(always)]
     fn render_todos_widget(
         highlight_symbol: &str,
         frame: &mut Frame,
         list_state: Option<&mut ListState>,
         todo_layout: Rect,
-        display_list: Vec<String>,
+        display_list: Vec<ListItem<'static>>,
         title: String,
     ) {
         match create_todo_widget(display_list, title, highlight_symbol) {
@@ -729,7 +1174,8 @@ impl<'a> TuiApp<'a> {
 
     #[inline]
     pub fn ui(&mut self, frame: &mut Frame, list_state: &mut ListState) {
-        let todo = self.todo_app.todo();
+        let todo = self.todo_app.todo().map(|todo| todo.clone());
+        let todo = todo.as_ref();
         if !self.args.minimal_render {
             list_state.select(Some(self.todo_app.index()));
         }
@@ -760,10 +1206,13 @@ impl<'a> TuiApp<'a> {
             .split(main_layout[self.args.enable_module as usize]);
         let is_editing = self.mode == Mode::Editing;
 
+        let has_breadcrumbs = !self.todo_app.is_root();
+
         let todo_and_textarea_layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Length(3 * is_editing as u16),
+                Constraint::Length(has_breadcrumbs as u16),
                 Constraint::Min(0),
             ])
             .split(todo_app_layout[0]);
@@ -774,7 +1223,13 @@ impl<'a> TuiApp<'a> {
         if is_editing {
             frame.render_widget(self.textarea.widget(), todo_and_textarea_layout[0]);
         }
-        self.render_current_todos_widget(frame, list_state, todo_and_textarea_layout[1]);
+        if has_breadcrumbs {
+            self.render_breadcrumbs(frame, todo_and_textarea_layout[1]);
+        }
+        self.render_current_todos_widget(frame, list_state, todo_and_textarea_layout[2]);
+        if let Some(lines) = self.registers_popup.as_ref() {
+            Self::render_registers_popup(frame, lines);
+        }
     }
 }
 
@@ -793,11 +1248,11 @@ pub enum TodoWidget<'a> {
     Paragraph(ratatui::widgets::Paragraph<'a>),
 }
 
-pub fn create_todo_widget(
-    display_list: Vec<String>,
+pub fn create_todo_widget<'a>(
+    display_list: Vec<ListItem<'static>>,
     title: String,
-    highlight_symbol: &str,
-) -> TodoWidget<'_> {
+    highlight_symbol: &'a str,
+) -> TodoWidget<'a> {
     if display_list.is_empty() {
         TodoWidget::Paragraph(Paragraph::new("No todo.").block(default_block(title)))
     } else {
@@ -829,17 +1284,21 @@ pub fn startup() -> io::Result<()> {
 }
 
 #[inline]
-pub fn run(app: &mut App, args: TuiArgs) -> io::Result<()> {
+pub fn run(app: &mut App, args: TuiArgs, config: &Config) -> io::Result<()> {
+    let keymap = Keymap::read(&args.keymap.clone().unwrap_or_else(Keymap::default_path))?;
+    let theme = Theme::from_config(&args.theme, config.table("themes"))?.respect_no_color();
     startup()?;
     let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
 
     let mut list_state = ListState::default().with_selected(Some(0));
-    let mut app = TuiApp::new(app, args);
+    let mut app = TuiApp::new(app, args, keymap, theme);
 
     loop {
         terminal.draw(|frame| app.ui(frame, &mut list_state))?;
 
         let operation = app.handle_key_and_return_operation()?;
+        // Any action could have done or undone a blocker
+        app.todo_app.update_blockers();
         match operation {
             HandlerOperation::Restart => {
                 startup();
@@ -0,0 +1,52 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+
+/// Longest a crumb's message is shown, in characters
+const MAX_CRUMB: usize = 24;
+const SEPARATOR: &str = " › ";
+const ELLIPSIS: &str = "…";
+
+/// A line of the numbered crumbs, like `0 Todos › 1 Release › 2 Changelog`. Long messages are
+/// cut, and crumbs after the first one are left out until the line fits in width.
+pub fn line(crumbs: &[&str], width: usize) -> String {
+    let mut crumbs: Vec<String> = crumbs
+        .iter()
+        .enumerate()
+        .map(|(level, crumb)| format!("{level} {}", truncate(crumb, MAX_CRUMB)))
+        .collect();
+    let mut line = crumbs.join(SEPARATOR);
+    while line.chars().count() > width && crumbs.len() > 2 {
+        if crumbs[1] == ELLIPSIS {
+            crumbs.remove(2);
+        } else {
+            crumbs[1] = ELLIPSIS.to_string();
+        }
+        line = crumbs.join(SEPARATOR);
+    }
+    truncate(&line, width)
+}
+
+#[inline]
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_string();
+    }
+    let mut truncated: String = s.chars().take(width.saturating_sub(1)).collect();
+    truncated.push_str(ELLIPSIS);
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        let crumbs = ["Todos", "Release", "Write the changelog of the release", "Tag"];
+        assert_eq!(
+            line(&crumbs, 80),
+            "0 Todos › 1 Release › 2 Write the changelog of … › 3 Tag"
+        );
+        assert_eq!(line(&crumbs, 40), "0 Todos › … › 3 Tag");
+        assert_eq!(line(&crumbs[..2], 10), "0 Todos ›…");
+    }
+}
@@ -0,0 +1,103 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+// }}}
+
+/// Commands tried for reading the clipboard when `--paste-command` isn't given
+const PASTE_COMMANDS: [&str; 3] = ["wl-paste -n", "xclip -o -selection clipboard", "pbpaste"];
+
+/// Copies text to the system clipboard with command (like `wl-copy` or `xclip -selection
+/// clipboard`), or with an OSC 52 escape sequence that the terminal handles (works over ssh)
+pub fn copy(text: &str, command: Option<&str>) -> io::Result<()> {
+    match command {
+        Some(command) => {
+            let mut child = build_command(command)?.stdin(Stdio::piped()).stderr(Stdio::null()).spawn()?;
+            child.stdin.take().unwrap().write_all(text.as_bytes())?;
+            let status = child.wait()?;
+            if !status.success() {
+                return Err(io::Error::other(format!("{command} failed with {status}")));
+            }
+            Ok(())
+        }
+        None => {
+            let mut stdout = io::stdout();
+            write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+            stdout.flush()
+        }
+    }
+}
+
+/// Reads the system clipboard with command, or the first of wl-paste, xclip and pbpaste that works
+pub fn paste(command: Option<&str>) -> io::Result<String> {
+    match command {
+        Some(command) => run(command),
+        None => PASTE_COMMANDS
+            .iter()
+            .find_map(|command| run(command).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no clipboard command found")),
+    }
+}
+
+fn run(command: &str) -> io::Result<String> {
+    let output = build_command(command)?.stderr(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!("{command} failed")));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Runs command with sh, so it can have quotes and pipes like the notify command
+fn build_command(command: &str) -> io::Result<Command> {
+    if command.trim().is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty clipboard command"));
+    }
+    let mut sh = Command::new("sh");
+    sh.arg("-c").arg(command);
+    Ok(sh)
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| group | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_CHARS[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("Hello there\n".as_bytes()), "SGVsbG8gdGhlcmUK");
+    }
+
+    #[test]
+    fn test_copy_command() -> io::Result<()> {
+        let path = std::env::temp_dir().join("c3-test-clipboard");
+        copy("Hello there", Some(&format!("cat > '{}'", path.display())))?;
+        assert_eq!(std::fs::read_to_string(&path)?, "Hello there");
+        assert_eq!(paste(Some(&format!("cat '{}'", path.display())))?, "Hello there");
+        std::fs::remove_file(path)?;
+        assert!(copy("Hello there", Some("false")).is_err());
+        assert!(copy("Hello there", Some(" ")).is_err());
+        Ok(())
+    }
+}
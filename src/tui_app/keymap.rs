@@ -0,0 +1,696 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+// }}}
+
+/// Everything a key can do in the normal mode, named like `toggle-done` in the keymap file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Action {
+    /// add todo to bottom
+    Add,
+    /// add todo to top
+    AddFirst,
+    /// toggle todo done
+    ToggleDone,
+    /// edit todo
+    Edit,
+    /// edit todo (move cursor to start)
+    EditAtStart,
+    /// edit messages of the todo list in $EDITOR
+    BatchEdit,
+    /// toggle show done
+    ToggleShowDone,
+    /// set todo priority
+    #[value(name = "priority-0")]
+    Priority0,
+    /// set todo priority
+    #[value(name = "priority-1")]
+    Priority1,
+    /// set todo priority
+    #[value(name = "priority-2")]
+    Priority2,
+    /// set todo priority
+    #[value(name = "priority-3")]
+    Priority3,
+    /// set todo priority
+    #[value(name = "priority-4")]
+    Priority4,
+    /// set todo priority
+    #[value(name = "priority-5")]
+    Priority5,
+    /// set todo priority
+    #[value(name = "priority-6")]
+    Priority6,
+    /// set todo priority
+    #[value(name = "priority-7")]
+    Priority7,
+    /// set todo priority
+    #[value(name = "priority-8")]
+    Priority8,
+    /// set todo priority
+    #[value(name = "priority-9")]
+    Priority9,
+    /// go down in todo list
+    GoDown,
+    /// go up in todo list
+    GoUp,
+    /// go top of todo list
+    GoTop,
+    /// go bottom of todo list
+    GoBottom,
+    /// increase todo priority
+    MoveDown,
+    /// decrease todo priority
+    MoveUp,
+    /// restrict todos by priority
+    RestrictPriority,
+    /// restrict todos by schedule day
+    RestrictSchedule,
+    /// restrict todos by `#tag` or `@context`
+    RestrictTag,
+    /// edit attributes of todo (`key:value key:value`)
+    EditAttributes,
+    /// make todo blocked by a todo of the whole tree (searched)
+    Block,
+    /// hide todos blocked by undone todos
+    ToggleHideBlocked,
+    /// move done todos that aren't scheduled (with their dependencies) of the list to the archive
+    Archive,
+    /// save todo with its whole dependency tree as a template
+    SaveTemplate,
+    /// add todo from a template (`release version=1.8`)
+    AddFromTemplate,
+    /// toggle daily
+    ToggleDaily,
+    /// toggle weekly
+    ToggleWeekly,
+    /// toggle resetting the todo list of a scheduled todo each time it recurs (checklists)
+    ToggleResetsList,
+    /// set custom schedule
+    SetSchedule,
+    /// set todo as a reminder (accepts dates like `today`, `+3d`, `in 2 weeks`, `fri`, `next mon`, `dec 24`, `2026-12` and `2026-12-24`)
+    SetReminder,
+    /// remove the schedule of todo, or bring the removed one back
+    ToggleSchedule,
+    /// delete todo
+    Delete,
+    /// add todo note
+    EditNote,
+    /// increase day done
+    IncreaseDay,
+    /// decrease day done
+    DecreaseDay,
+    /// add todo dependency
+    AddDependency,
+    /// go in depedency/add todo dependency
+    GoInOrAddDependency,
+    /// go in depedency
+    GoIn,
+    /// go back to parent
+    GoBack,
+    /// go back to the root todo list
+    GoRoot,
+    /// go to the list of the breadcrumb numbered by the next key (`0` is the root list)
+    GoToLevel,
+    /// delete todo dependency/note
+    RemoveDependency,
+    /// move todo to the list of its parent
+    MoveToParent,
+    /// move todo into the todo list of a todo of the current list (searched)
+    MoveInto,
+    /// move todo into the todo list of a todo of the whole tree (searched)
+    MoveTo,
+    /// move todo with its dependencies to another todo file
+    MoveToFile,
+    /// start or stop selecting the todos between the todo and the cursor (visual mode)
+    Visual,
+    /// select or unselect todo, the selected todos are deleted, cut, yanked, moved, marked done, scheduled and prioritised together
+    ToggleMark,
+    /// unselect all todos
+    ClearSelection,
+    /// use a register (`a`-`z`, `0`-`9`, `"` is the default one) for the next cut, yank or paste, like `"a y`
+    Register,
+    /// show the registers
+    Registers,
+    /// cut todo to the register
+    Cut,
+    /// yank todo to the register, registers are kept after c3 exits
+    Yank,
+    /// paste a copy of the todos of the register, with their own dependencies
+    Paste,
+    /// paste the todos of the register sharing the todo lists of the yanked todos of this tree, changes are seen from both
+    PasteAsLink,
+    /// copy todo to the system clipboard, as `--clipboard-format` text (messages by default)
+    CopyToClipboard,
+    /// add todos from the lines of the system clipboard (todo file lines, markdown lists or messages)
+    PasteFromClipboard,
+    /// show the whole tree in the list (outline), lists of todos can be folded
+    ToggleOutline,
+    /// unfold the todo list of todo in the outline
+    OpenFold,
+    /// fold the todo list of todo, or the list todo is in, in the outline
+    CloseFold,
+    /// fold or unfold the todo list of todo in the outline
+    ToggleFold,
+    /// unfold every todo list in the outline
+    OpenAllFolds,
+    /// fold every todo list in the outline
+    CloseAllFolds,
+    /// enable module
+    ToggleModule,
+    /// show or hide the dependency of todo on the right
+    ToggleDependencyView,
+    /// search current list for todo
+    Search,
+    /// search the whole tree for todo (tree search), `#tag` and `@context` words only match whole tags
+    TreeSearch,
+    /// tree search next
+    SearchNext,
+    /// open nnn file picker to choose a file to append to current list
+    AppendFile,
+    /// open nnn file picker to choose a file to output current list to
+    OutputFile,
+    /// open nnn file picker to choose a file to open
+    OpenFile,
+    /// suspend (send current c3 to background, only in *nix)
+    Suspend,
+    /// write changes to file
+    Write,
+    /// read from file (discard changes)
+    Read,
+    /// undo
+    Undo,
+    /// redo
+    Redo,
+    /// quit (asks to write changes)
+    Quit,
+    /// skip current
+    PotatoSkip,
+    /// increase timer
+    PotatoIncreaseTimer,
+    /// decrease timer
+    PotatoDecreaseTimer,
+    /// increase pomodoro count
+    PotatoIncreasePomodoro,
+    /// decrease pomodoro count
+    PotatoDecreasePomodoro,
+    /// toggle pause
+    PotatoTogglePause,
+    /// quit
+    PotatoQuit,
+    /// restart
+    PotatoRestart,
+    /// next server
+    PotatoNextServer,
+    /// prev server
+    PotatoPrevServer,
+}
+
+impl Action {
+    /// Actions of the potato module, they only work while the module is enabled
+    #[inline]
+    pub fn is_module(&self) -> bool {
+        matches!(
+            self,
+            Self::PotatoSkip
+                | Self::PotatoIncreaseTimer
+                | Self::PotatoDecreaseTimer
+                | Self::PotatoIncreasePomodoro
+                | Self::PotatoDecreasePomodoro
+                | Self::PotatoTogglePause
+                | Self::PotatoQuit
+                | Self::PotatoRestart
+                | Self::PotatoNextServer
+                | Self::PotatoPrevServer
+        )
+    }
+
+    /// Actions that change the todos, they don't work while the todo file is read only (like
+    /// the archive)
+    #[inline]
+    pub fn is_mutating(&self) -> bool {
+        self.priority().is_some()
+            || matches!(
+                self,
+                Self::Add
+                    | Self::AddFirst
+                    | Self::ToggleDone
+                    | Self::Edit
+                    | Self::EditAtStart
+                    | Self::BatchEdit
+                    | Self::MoveDown
+                    | Self::MoveUp
+                    | Self::EditAttributes
+                    | Self::Block
+                    | Self::Archive
+                    | Self::AddFromTemplate
+                    | Self::ToggleDaily
+                    | Self::ToggleWeekly
+                    | Self::ToggleResetsList
+                    | Self::SetSchedule
+                    | Self::SetReminder
+                    | Self::ToggleSchedule
+                    | Self::Delete
+                    | Self::EditNote
+                    | Self::IncreaseDay
+                    | Self::DecreaseDay
+                    | Self::AddDependency
+                    | Self::GoInOrAddDependency
+                    | Self::RemoveDependency
+                    | Self::MoveToParent
+                    | Self::MoveInto
+                    | Self::MoveTo
+                    | Self::MoveToFile
+                    | Self::Cut
+                    | Self::Paste
+                    | Self::PasteAsLink
+                    | Self::PasteFromClipboard
+                    | Self::AppendFile
+                    | Self::Undo
+                    | Self::Redo
+            )
+    }
+
+    #[inline]
+    pub fn priority(&self) -> Option<u8> {
+        let priorities = [
+            Self::Priority0,
+            Self::Priority1,
+            Self::Priority2,
+            Self::Priority3,
+            Self::Priority4,
+            Self::Priority5,
+            Self::Priority6,
+            Self::Priority7,
+            Self::Priority8,
+            Self::Priority9,
+        ];
+        priorities.iter().position(|action| action == self).map(|priority| priority as u8)
+    }
+
+    #[inline]
+    fn name(&self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    fn description(&self) -> String {
+        self.to_possible_value()
+            .and_then(|value| value.get_help().map(ToString::to_string))
+            .unwrap_or_default()
+    }
+}
+
+const DEFAULT_BINDINGS: &[(&str, Action)] = &[
+    ("a", Action::Add),
+    ("A", Action::AddFirst),
+    ("space", Action::ToggleDone),
+    ("e", Action::Edit),
+    ("E", Action::EditAtStart),
+    ("r", Action::BatchEdit),
+    ("!", Action::ToggleShowDone),
+    ("0", Action::Priority0),
+    ("1", Action::Priority1),
+    ("2", Action::Priority2),
+    ("3", Action::Priority3),
+    ("4", Action::Priority4),
+    ("5", Action::Priority5),
+    ("6", Action::Priority6),
+    ("7", Action::Priority7),
+    ("8", Action::Priority8),
+    ("9", Action::Priority9),
+    ("j", Action::GoDown),
+    ("down", Action::GoDown),
+    ("k", Action::GoUp),
+    ("up", Action::GoUp),
+    ("g", Action::GoTop),
+    ("home", Action::GoTop),
+    ("G", Action::GoBottom),
+    ("end", Action::GoBottom),
+    ("J", Action::MoveDown),
+    ("K", Action::MoveUp),
+    ("@", Action::RestrictPriority),
+    ("%", Action::RestrictSchedule),
+    ("#", Action::RestrictTag),
+    (":", Action::EditAttributes),
+    ("b", Action::Block),
+    ("B", Action::ToggleHideBlocked),
+    ("X", Action::Archive),
+    ("Ctrl+t", Action::SaveTemplate),
+    ("N", Action::AddFromTemplate),
+    ("d", Action::ToggleDaily),
+    ("W", Action::ToggleWeekly),
+    ("*", Action::ToggleResetsList),
+    ("S", Action::SetSchedule),
+    ("m", Action::SetReminder),
+    ("M", Action::ToggleSchedule),
+    ("D", Action::Delete),
+    (">", Action::EditNote),
+    ("i", Action::IncreaseDay),
+    ("I", Action::DecreaseDay),
+    ("t", Action::AddDependency),
+    ("l", Action::GoInOrAddDependency),
+    ("right", Action::GoInOrAddDependency),
+    ("enter", Action::GoIn),
+    ("h", Action::GoBack),
+    ("left", Action::GoBack),
+    ("~", Action::GoRoot),
+    ("`", Action::GoToLevel),
+    ("T", Action::RemoveDependency),
+    ("<", Action::MoveToParent),
+    ("}", Action::MoveInto),
+    ("{", Action::MoveTo),
+    ("F", Action::MoveToFile),
+    ("v", Action::Visual),
+    ("V", Action::ToggleMark),
+    ("esc", Action::ClearSelection),
+    ("\"", Action::Register),
+    ("'", Action::Registers),
+    ("x", Action::Cut),
+    ("y", Action::Yank),
+    ("p", Action::Paste),
+    ("Ctrl+p", Action::PasteAsLink),
+    ("Y", Action::CopyToClipboard),
+    ("Ctrl+v", Action::PasteFromClipboard),
+    ("Z", Action::ToggleOutline),
+    ("z o", Action::OpenFold),
+    ("z c", Action::CloseFold),
+    ("z a", Action::ToggleFold),
+    ("z R", Action::OpenAllFolds),
+    ("z M", Action::CloseAllFolds),
+    ("P", Action::ToggleModule),
+    ("]", Action::ToggleDependencyView),
+    ("/", Action::Search),
+    ("?", Action::TreeSearch),
+    ("n", Action::SearchNext),
+    ("o", Action::AppendFile),
+    ("O", Action::OutputFile),
+    ("Ctrl+o", Action::OpenFile),
+    ("Ctrl+z", Action::Suspend),
+    ("w", Action::Write),
+    ("R", Action::Read),
+    ("u", Action::Undo),
+    ("Ctrl+r", Action::Redo),
+    ("q", Action::Quit),
+    ("s", Action::PotatoSkip),
+    ("H", Action::PotatoIncreaseTimer),
+    ("L", Action::PotatoDecreaseTimer),
+    ("+", Action::PotatoIncreasePomodoro),
+    ("=", Action::PotatoIncreasePomodoro),
+    ("-", Action::PotatoDecreasePomodoro),
+    ("c", Action::PotatoTogglePause),
+    ("C", Action::PotatoQuit),
+    ("f", Action::PotatoRestart),
+    (".", Action::PotatoNextServer),
+    (",", Action::PotatoPrevServer),
+];
+
+/// A key with its modifiers, like `Ctrl+r`. Shift is a part of the character for character
+/// keys (`J` instead of `Shift+j`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("space", KeyCode::Char(' ')),
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    Toml(String),
+    UnknownKey(String),
+    UnknownAction(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Toml(error) => write!(f, "{error}"),
+            Self::UnknownKey(key) => write!(f, "unknown key \"{key}\""),
+            Self::UnknownAction(action) => write!(f, "unknown action \"{action}\""),
+        }
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if let KeyCode::Char(_) = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || Error::UnknownKey(s.to_string());
+        let (modifier_names, key) = if s == "+" {
+            ("", "+")
+        } else if let Some(modifier_names) = s.strip_suffix("++") {
+            (modifier_names, "+")
+        } else {
+            s.rsplit_once('+').unwrap_or(("", s))
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= match name.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(unknown()),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let key = key.to_lowercase();
+                match KEY_NAMES.iter().find(|(name, _)| *name == key) {
+                    Some((_, code)) => *code,
+                    None => match key.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                        Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                        _ => return Err(unknown()),
+                    },
+                }
+            }
+        };
+        if let KeyCode::Char(c) = code {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                modifiers.remove(KeyModifiers::SHIFT);
+                return Ok(Self {
+                    code: KeyCode::Char(c.to_ascii_uppercase()),
+                    modifiers,
+                });
+            }
+        }
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}")?;
+            }
+        }
+        match KEY_NAMES.iter().find(|(_, code)| *code == self.code) {
+            Some((name, _)) => write!(f, "{name}"),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{c}"),
+                KeyCode::F(n) => write!(f, "f{n}"),
+                code => write!(f, "{code:?}"),
+            },
+        }
+    }
+}
+
+/// Parses key sequences like `g g` or `Ctrl+x d`
+fn parse_sequence(s: &str) -> Result<Vec<KeyChord>, Error> {
+    let sequence = s.split_whitespace().map(str::parse).collect::<Result<Vec<_>, _>>()?;
+    if sequence.is_empty() {
+        return Err(Error::UnknownKey(s.to_string()));
+    }
+    Ok(sequence)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Lookup {
+    Action(Action),
+    /// The keys are the start of a longer sequence
+    Prefix,
+    None,
+}
+
+/// Key sequences of the actions, in the order they're shown in
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Vec<KeyChord>, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_BINDINGS
+                .iter()
+                .map(|(keys, action)| (parse_sequence(keys).unwrap(), *action))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// `$XDG_CONFIG_HOME/c3/keymap.toml`
+    #[inline]
+    pub fn default_path() -> PathBuf {
+        c3::fileio::config_dir().join("keymap.toml")
+    }
+
+    /// The default keymap with the bindings of the file, if it exists
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let mut keymap = Self::default();
+        if path.is_file() {
+            keymap
+                .bind_toml(&fs::read_to_string(path)?)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {error}", path.display())))?;
+        }
+        Ok(keymap)
+    }
+
+    /// Binds the `"keys" = "action"` pairs of a TOML document, `"none"` unbinds the keys
+    pub fn bind_toml(&mut self, content: &str) -> Result<(), Error> {
+        let table: toml::Table = content.parse().map_err(|error: toml::de::Error| Error::Toml(error.message().to_string()))?;
+        for (keys, action) in table {
+            let action = action.as_str().ok_or_else(|| Error::UnknownAction(action.to_string()))?;
+            let action = match action {
+                "none" => None,
+                action => Some(Action::from_str(action, true).map_err(|_| Error::UnknownAction(action.to_string()))?),
+            };
+            self.bind(parse_sequence(&keys)?, action);
+        }
+        Ok(())
+    }
+
+    /// Binds the sequence to the action. Bindings that the sequence starts with or that start
+    /// with it are removed, as they couldn't be told apart.
+    pub fn bind(&mut self, sequence: Vec<KeyChord>, action: Option<Action>) {
+        self.bindings
+            .retain(|(keys, _)| !keys.starts_with(&sequence) && !sequence.starts_with(keys));
+        if let Some(action) = action {
+            self.bindings.push((sequence, action));
+        }
+    }
+
+    pub fn lookup(&self, keys: &[KeyChord]) -> Lookup {
+        let mut lookup = Lookup::None;
+        for (sequence, action) in self.bindings.iter() {
+            if sequence == keys {
+                return Lookup::Action(*action);
+            }
+            if sequence.starts_with(keys) {
+                lookup = Lookup::Prefix;
+            }
+        }
+        lookup
+    }
+
+    /// Markdown table of the keys of the actions (module actions if module is true) with their
+    /// names. Keys of the actions with the same description share a row.
+    pub fn markdown(&self, module: bool) -> String {
+        let mut rows: Vec<(String, Vec<String>, Vec<String>)> = vec![];
+        for (sequence, action) in self.bindings.iter().filter(|(_, action)| action.is_module() == module) {
+            let keys = sequence.iter().map(ToString::to_string).collect::<Vec<_>>().join(" ");
+            let (description, name) = (action.description(), action.name());
+            match rows.iter_mut().find(|(row, _, _)| *row == description) {
+                Some((_, row_keys, names)) => {
+                    row_keys.push(keys);
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                None => rows.push((description, vec![keys], vec![name])),
+            }
+        }
+        let mut table = String::from("| key | action | name |\n|---|---|---|\n");
+        for (description, keys, names) in rows {
+            table.push_str(&format!("| {} | {description} | {} |\n", keys.join(", "), names.join(", ")));
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_chord() {
+        let chord: KeyChord = "ctrl+r".parse().unwrap();
+        assert_eq!(chord, KeyChord::from(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)));
+        assert_eq!(chord.to_string(), "Ctrl+r");
+        let chord: KeyChord = "shift+j".parse().unwrap();
+        assert_eq!(chord, KeyChord::from(KeyEvent::new(KeyCode::Char('J'), KeyModifiers::SHIFT)));
+        assert_eq!("alt++".parse::<KeyChord>().unwrap().to_string(), "Alt++");
+        assert_eq!("f5".parse::<KeyChord>().unwrap().code, KeyCode::F(5));
+        assert!("hyper+x".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn test_bind_toml() {
+        let mut keymap = Keymap::default();
+        keymap.bind_toml("\"g g\" = \"go-top\"\ns = \"none\"\nU = \"undo\"").unwrap();
+        let g: Vec<KeyChord> = vec!["g".parse().unwrap()];
+        assert_eq!(keymap.lookup(&g), Lookup::Prefix);
+        assert_eq!(keymap.lookup(&parse_sequence("g g").unwrap()), Lookup::Action(Action::GoTop));
+        assert_eq!(keymap.lookup(&parse_sequence("s").unwrap()), Lookup::None);
+        assert_eq!(keymap.lookup(&parse_sequence("U").unwrap()), Lookup::Action(Action::Undo));
+        assert_eq!(keymap.lookup(&parse_sequence("u").unwrap()), Lookup::Action(Action::Undo));
+        assert_eq!(
+            keymap.bind_toml("x = \"explode\""),
+            Err(Error::UnknownAction(String::from("explode")))
+        );
+    }
+
+    #[test]
+    fn test_readme_is_generated() {
+        let readme = include_str!("../../README.md");
+        let keymap = Keymap::default();
+        assert!(readme.contains(&keymap.markdown(false)));
+        assert!(readme.contains(&keymap.markdown(true)));
+    }
+}
@@ -0,0 +1,277 @@
+// vim:fileencoding=utf-8:foldmethod=marker
+// imports {{{
+use c3::todo_app::Todo;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::ListItem;
+use std::env;
+use std::fmt;
+use std::io;
+// }}}
+
+/// Styles of the todos in the TUI. Styles are written like `"light-red bold"` or
+/// `"crossed-out on #202020"`, see parse_style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    priorities: [Style; 10],
+    done: Style,
+    overdue: Style,
+    /// Scheduled todos that missed a period use the first style, two periods the second, ...
+    abandoned: Vec<Style>,
+    /// Todos selected with the visual mode or marked
+    selected: Style,
+    note_icon: String,
+    list_icon: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    UnknownTheme(String),
+    UnknownKey(String),
+    InvalidStyle(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTheme(name) => write!(f, "unknown theme \"{name}\""),
+            Self::UnknownKey(key) => write!(f, "unknown theme key \"{key}\""),
+            Self::InvalidStyle(style) => write!(f, "invalid style \"{style}\""),
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let mut priorities = [Style::default(); 10];
+        priorities[1] = Style::new().fg(Color::LightMagenta);
+        priorities[2] = Style::new().fg(Color::LightCyan);
+        priorities[3] = Style::new().fg(Color::LightBlue);
+        Self {
+            priorities,
+            done: Style::new().add_modifier(Modifier::DIM),
+            overdue: Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+            abandoned: vec![
+                Style::new().fg(Color::Yellow),
+                Style::new().fg(Color::LightRed),
+                Style::new().fg(Color::Red),
+            ],
+            selected: Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            note_icon: String::new(),
+            list_icon: String::new(),
+        }
+    }
+}
+
+const MODIFIERS: &[(&str, Modifier)] = &[
+    ("bold", Modifier::BOLD),
+    ("dim", Modifier::DIM),
+    ("italic", Modifier::ITALIC),
+    ("underlined", Modifier::UNDERLINED),
+    ("reversed", Modifier::REVERSED),
+    ("crossed-out", Modifier::CROSSED_OUT),
+];
+
+/// Parses styles made of modifiers (bold, dim, italic, underlined, reversed, crossed-out) and
+/// colors (names like light-red, #rrggbb or 0-255), a color after `on` is the background.
+/// `none` is the default style.
+pub fn parse_style(s: &str) -> Result<Style, Error> {
+    let mut style = Style::default();
+    let mut words = s.split_whitespace();
+    while let Some(word) = words.next() {
+        let word = word.to_lowercase();
+        if let Some((_, modifier)) = MODIFIERS.iter().find(|(name, _)| *name == word) {
+            style = style.add_modifier(*modifier);
+            continue;
+        }
+        style = match word.as_str() {
+            "none" => style,
+            "on" => {
+                let color = words.next().and_then(|color| color.parse().ok());
+                style.bg(color.ok_or_else(|| Error::InvalidStyle(s.to_string()))?)
+            }
+            color => style.fg(color.parse().map_err(|_| Error::InvalidStyle(s.to_string()))?),
+        };
+    }
+    Ok(style)
+}
+
+impl Theme {
+    /// A theme without any style or icon, except for selected todos
+    #[inline]
+    pub fn plain() -> Self {
+        Self {
+            priorities: [Style::default(); 10],
+            done: Style::default(),
+            overdue: Style::default(),
+            abandoned: vec![],
+            selected: Self::default().selected,
+            note_icon: String::new(),
+            list_icon: String::new(),
+        }
+    }
+
+    /// Built in themes are `default` and `plain`, themes of the config (`[themes.<name>]`)
+    /// change the default one
+    pub fn from_config(name: &str, themes: Option<&toml::Table>) -> Result<Self, Error> {
+        match themes.and_then(|themes| themes.get(name)) {
+            Some(table) => {
+                let mut theme = Self::default();
+                let table = table.as_table().ok_or_else(|| Error::UnknownTheme(name.to_string()))?;
+                theme.set_toml(table)?;
+                Ok(theme)
+            }
+            None => match name {
+                "default" => Ok(Self::default()),
+                "plain" => Ok(Self::plain()),
+                name => Err(Error::UnknownTheme(name.to_string())),
+            },
+        }
+    }
+
+    fn set_toml(&mut self, table: &toml::Table) -> Result<(), Error> {
+        let style = |value: &toml::Value| -> Result<Style, Error> {
+            value
+                .as_str()
+                .ok_or_else(|| Error::InvalidStyle(value.to_string()))
+                .and_then(parse_style)
+        };
+        for (key, value) in table {
+            match key.as_str() {
+                "done" => self.done = style(value)?,
+                "overdue" => self.overdue = style(value)?,
+                "selected" => self.selected = style(value)?,
+                "abandoned" => {
+                    self.abandoned = match value.as_array() {
+                        Some(values) => values.iter().map(style).collect::<Result<_, _>>()?,
+                        None => vec![style(value)?],
+                    }
+                }
+                "note-icon" | "list-icon" => {
+                    let icon = value.as_str().ok_or_else(|| Error::InvalidStyle(value.to_string()))?;
+                    match key.as_str() {
+                        "note-icon" => self.note_icon = icon.to_string(),
+                        _ => self.list_icon = icon.to_string(),
+                    }
+                }
+                key => {
+                    let priority = key
+                        .strip_prefix("priority-")
+                        .and_then(|priority| priority.parse::<usize>().ok())
+                        .filter(|&priority| priority < 10)
+                        .ok_or_else(|| Error::UnknownKey(key.to_string()))?;
+                    self.priorities[priority] = style(value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the colors if NO_COLOR is set, see no-color.org
+    pub fn respect_no_color(mut self) -> Self {
+        if env::var_os("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()) {
+            self.remove_colors();
+        }
+        self
+    }
+
+    fn remove_colors(&mut self) {
+        let remove = |style: &mut Style| {
+            style.fg = None;
+            style.bg = None;
+        };
+        self.priorities.iter_mut().for_each(remove);
+        self.abandoned.iter_mut().for_each(remove);
+        remove(&mut self.done);
+        remove(&mut self.overdue);
+        remove(&mut self.selected);
+    }
+
+    pub fn style(&self, todo: &Todo) -> Style {
+        let mut style = self.priorities[todo.priority() as usize % 10];
+        if todo.done() {
+            return style.patch(self.done);
+        }
+        if let Some(schedule) = todo.schedule.as_ref() {
+            if schedule.is_reminder() {
+                if schedule.days_diff() < 0 {
+                    style = style.patch(self.overdue);
+                }
+            } else {
+                let missed = (todo.abandonment_coefficient() - 1.).ceil();
+                if missed >= 1. && !self.abandoned.is_empty() {
+                    let level = (missed as usize).min(self.abandoned.len());
+                    style = style.patch(self.abandoned[level - 1]);
+                }
+            }
+        }
+        style
+    }
+
+    #[inline]
+    fn icon(&self, todo: &Todo) -> &str {
+        match todo.dependency.as_ref() {
+            Some(dependency) if dependency.is_note() => &self.note_icon,
+            Some(dependency) if dependency.is_list() => &self.list_icon,
+            _ => "",
+        }
+    }
+
+    #[inline]
+    pub fn item(&self, todo: &Todo, display: String, selected: bool) -> ListItem<'static> {
+        ListItem::new(format!("{}{display}", self.icon(todo))).style(self.item_style(todo, selected))
+    }
+
+    #[inline]
+    fn item_style(&self, todo: &Todo, selected: bool) -> Style {
+        let style = self.style(todo);
+        if selected {
+            style.patch(self.selected)
+        } else {
+            style
+        }
+    }
+
+    /// Item of the todo after the tree lines of the outline, which aren't styled
+    pub fn tree_item(&self, todo: &Todo, prefix: String, display: String, selected: bool) -> ListItem<'static> {
+        let display = Span::styled(format!("{}{display}", self.icon(todo)), self.item_style(todo, selected));
+        ListItem::new(Line::from(vec![Span::raw(prefix), display]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(
+            parse_style("light-red bold on #202020"),
+            Ok(Style::new().fg(Color::LightRed).bg(Color::Rgb(32, 32, 32)).add_modifier(Modifier::BOLD))
+        );
+        assert_eq!(parse_style("none"), Ok(Style::default()));
+        assert!(parse_style("sparkly").is_err());
+    }
+
+    #[test]
+    fn test_style() {
+        let themes: toml::Table = "[mine]\ndone = \"crossed-out\"\npriority-1 = \"green\"".parse().unwrap();
+        let theme = Theme::from_config("mine", Some(&themes)).unwrap();
+        let mut todo = Todo::default();
+        todo.set_priority(1);
+        assert_eq!(theme.style(&todo).fg, Some(Color::Green));
+        todo.set_done(true);
+        assert!(theme.style(&todo).add_modifier.contains(Modifier::CROSSED_OUT));
+        assert_eq!(Theme::from_config("other", Some(&themes)), Err(Error::UnknownTheme(String::from("other"))));
+        let mut theme = Theme::default();
+        theme.remove_colors();
+        assert_eq!(theme.priorities[1], Style::default());
+        assert_eq!(theme.done, Theme::default().done);
+    }
+}
@@ -1,5 +1,5 @@
-use std::{collections::VecDeque, ops::Not};
-use c3::todo_app::{App, Restriction, TodoList};
+use std::ops::Not;
+use c3::todo_app::{is_tag, App, Restriction, Todo, TodoList};
 
 #[derive(Clone, Default)]
 struct SearchPosition {
@@ -24,6 +24,14 @@ impl TreeSearch {
         })
     }
 
+    /// `#tag` and `@context` words of the query have to be tags of the todo, the rest is
+    /// matched against the message
+    #[inline]
+    pub fn matches(todo: &Todo, query: &str) -> bool {
+        let (tags, words): (Vec<&str>, Vec<&str>) = query.split_whitespace().partition(|word| is_tag(word));
+        tags.iter().all(|tag| todo.has_tag(tag)) && todo.matches(&words.join(" "))
+    }
+
     #[inline]
     pub fn search(&mut self, query: String, todo_list: &TodoList, restriction: Restriction) {
         self.positions = vec![];
@@ -32,27 +40,32 @@ impl TreeSearch {
         if query.is_empty() {
             return;
         }
-        let mut lists: VecDeque<(Vec<usize>, &TodoList)> =
-            VecDeque::from([(vec![], todo_list)]);
-        while let Some((indices, current_list)) = lists.pop_back() {
-            let mut matching_indices: Vec<usize> = vec![];
-            for (i, todo) in current_list.filter(&restriction).enumerate() {
-                let mut todo_indices = indices.clone();
-                todo_indices.push(i);
-                if todo.matches(&query) {
-                    matching_indices.push(i)
-                }
-                if let Some(list) = todo.dependency.as_ref().and_then(|dep| dep.todo_list()) {
-                    lists.push_back((todo_indices, list))
-                }
+        self.search_list(&query, vec![], todo_list, &restriction);
+    }
+
+    /// Searches the list at indices and then its dependencies, the last one first
+    fn search_list(&mut self, query: &str, indices: Vec<usize>, current_list: &TodoList, restriction: &Restriction) {
+        let mut matching_indices: Vec<usize> = vec![];
+        let mut lists = vec![];
+        for (i, todo) in current_list.filter(restriction).enumerate() {
+            let mut todo_indices = indices.clone();
+            todo_indices.push(i);
+            if Self::matches(todo, query) {
+                matching_indices.push(i)
             }
-            if !matching_indices.is_empty() {
-                self.positions.push(SearchPosition {
-                    tree_path: indices.to_vec(),
-                    matching_indices,
-                })
+            if let Some(list) = todo.dependency.as_ref().and_then(|dep| dep.todo_list()) {
+                lists.push((todo_indices, list))
             }
         }
+        if !matching_indices.is_empty() {
+            self.positions.push(SearchPosition {
+                tree_path: indices,
+                matching_indices,
+            })
+        }
+        for (indices, list) in lists.into_iter().rev() {
+            self.search_list(query, indices, &list, restriction);
+        }
     }
 
     #[inline]
@@ -76,8 +89,7 @@ impl TreeSearch {
     #[inline]
     pub fn set_to_app(&self, todo_app:&mut App) {
         if let Some((index, path)) =  self.current_tree_position() {
-            todo_app.index = index;
-            todo_app.tree_path.clone_from(path);
+            todo_app.set_tree_position(path.clone(), index);
         }
     }
 }
@@ -96,10 +108,19 @@ mod test {
         remove_dir_all(dir)?;
         let query = String::from("nod");
         let mut tree_search = TreeSearch::default();
-        tree_search.search(query, app.current_list(), Rc::clone(app.get_restriction()));
+        tree_search.search(query, &app.current_list(), Rc::clone(app.get_restriction()));
         let position = &tree_search.positions[0];
         assert_eq!(position.tree_path, vec![2, 0]);
         assert_eq!(position.matching_indices, vec![0]);
         Ok(())
     }
+
+    #[test]
+    fn test_tag_matches() {
+        let todo = Todo::new(String::from("write the report #work"), 1);
+        assert!(TreeSearch::matches(&todo, "#work"));
+        assert!(TreeSearch::matches(&todo, "report #Work"));
+        assert!(!TreeSearch::matches(&todo, "#wor"));
+        assert!(!TreeSearch::matches(&todo, "#work email"));
+    }
 }